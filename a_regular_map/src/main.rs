@@ -1,37 +1,40 @@
 use std::collections::{HashMap, VecDeque};
-use std::env;
 
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(20, solve);
+}
 
-    let input: String = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: String = FileReader::new().read_from_file(input_file)?;
 
     let mut graph = Graph::new();
-    graph.build(&input);
+    graph
+        .build(&input)
+        .context(ErrorKind::Parse, "Error building map")?;
 
     let (farthest_node, farthest_dist) = graph.find_farthest_node(Position::new(0, 0));
     let distance_at_least_1000 = graph.find_nodes_farther_than(Position::new(0, 0), 1000);
 
-    println!("{}", graph);
-    println!(
-        "Farthest node: {:?}, distance {}",
-        farthest_node, farthest_dist
+    output.diagnostic(&graph);
+    output.answer(
+        1,
+        farthest_dist,
+        format!(
+            "Farthest node: {:?}, distance {}",
+            farthest_node, farthest_dist
+        ),
+    );
+    output.answer(
+        2,
+        distance_at_least_1000.len(),
+        format!("Nodes farther than 1000: {}", distance_at_least_1000.len()),
     );
-    println!("Nodes farther than 1000: {}", distance_at_least_1000.len());
+
+    Ok(())
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -178,12 +181,12 @@ impl Graph {
         }
     }
 
-    fn build(&mut self, input: &str) {
+    fn build(&mut self, input: &str) -> Result<(), String> {
         if !input.starts_with('^') || !input.ends_with('$') {
-            panic!("invalid input, missing start/end token");
+            return Err(String::from("invalid input, missing start/end token"));
         }
 
-        self.parse(Position::new(0, 0), input[1..].as_bytes());
+        self.parse(Position::new(0, 0), input[1..].as_bytes())
     }
 
     fn find_farthest_node(&self, from: Position) -> (Position, usize) {
@@ -255,7 +258,7 @@ impl Graph {
         distances
     }
 
-    fn parse(&mut self, start_position: Position, chars: &[u8]) {
+    fn parse(&mut self, start_position: Position, chars: &[u8]) -> Result<(), String> {
         let mut items = SortedQueueSet::new();
         items.insert(Item {
             chars,
@@ -269,14 +272,16 @@ impl Graph {
             }
 
             let current_item = items.pop_front().unwrap();
-            let new_items = self.process(current_item);
+            let new_items = self.process(current_item)?;
             for item in new_items {
                 items.insert(item);
             }
         }
+
+        Ok(())
     }
 
-    fn process<'a>(&mut self, item: Item<'a>) -> Vec<Item<'a>> {
+    fn process<'a>(&mut self, item: Item<'a>) -> Result<Vec<Item<'a>>, String> {
         let mut items = Vec::new();
 
         let c = item.chars[item.cursor];
@@ -360,10 +365,10 @@ impl Graph {
             b'$' => {
                 // nothing to do (item will be consumed, but not generate any new ones)
             }
-            c => panic!("invalid token {}", c),
+            c => return Err(format!("invalid token {}", c as char)),
         }
 
-        items
+        Ok(items)
     }
 
     fn add_edge(&mut self, from: Position, to: Position) {
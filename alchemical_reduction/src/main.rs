@@ -1,80 +1,459 @@
-use std::collections::VecDeque;
-use std::env;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::thread;
 
+use util::cli::{self, Output, SubcommandArgs};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
+use util::random::Rng;
+use util::string::graphemes;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
+    cli::run_with_subcommands(
+        5,
+        solve,
+        Some(generate),
+        &[("reduce", reduce_command), ("removals", removals_command)],
+    );
+}
+
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: String = FileReader::new().read_from_file(input_file)?;
+    let input = input.trim();
+    let rules = ReactionRules::case_pairs();
+
+    let remaining_units = rules.reduce(input, None).units;
+    output.answer(
+        1,
+        remaining_units,
+        format!("Remaining units: {}", remaining_units),
+    );
+
+    let (problematic_unit, shortest_polymer) = find_shortest_polymer(&rules, input);
+
+    output.answer(
+        2,
+        shortest_polymer,
+        format!(
+            "Shortest polymer: Remove {} => Resulting length: {}",
+            problematic_unit, shortest_polymer
+        ),
+    );
+
+    Ok(())
+}
+
+/// Reduces the polymer in the input file and prints the result, together with the number of
+/// reactions per unit type.
+///
+/// Usage: `reduce <input file> [--rules <rules file>] [--remove <unit type>]`. See
+/// `ReactionRules` for the format of the rules file; without it, the rules of the puzzle apply.
+fn reduce_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &[], &["rules", "remove"])?;
+    let rules = read_rules(args.value("rules")?)?;
+    let remove = args.value::<String>("remove")?;
+    let input: String = FileReader::new().read_from_file(args.input_file())?;
+
+    let reduction = rules.reduce(input.trim(), remove.as_deref());
+    println!("{}", reduction.polymer);
+    println!("Remaining units: {}", reduction.units);
+    for (unit_type, count) in reduction.reactions.iter() {
+        println!("Reactions of {}: {}", unit_type, count);
+    }
+
+    Ok(())
+}
+
+/// Ranks the removals of up to `--max-types` unit types (default: 1) by the length of the
+/// resulting polymer and prints the best `--top` (default: 10) of them.
+///
+/// Usage: `removals <input file> [--rules <rules file>] [--max-types <k>] [--top <n>]`.
+fn removals_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &[], &["rules", "max-types", "top"])?;
+    let rules = read_rules(args.value("rules")?)?;
+    let max_types = args.value("max-types")?.unwrap_or(1);
+    let top = args.value("top")?.unwrap_or(10);
+    let input: String = FileReader::new().read_from_file(args.input_file())?;
+
+    for (rank, removal) in rank_removals(&rules, input.trim(), max_types)
+        .iter()
+        .take(top)
+        .enumerate()
+    {
+        println!(
+            "{:>3}. Remove {} => Resulting length: {}",
+            rank + 1,
+            removal.unit_types.join(", "),
+            removal.units
+        );
+    }
+
+    Ok(())
+}
+
+/// Reads the reaction rules from `rules_file`, or returns the rules of the puzzle if no file is
+/// given.
+fn read_rules(rules_file: Option<String>) -> Result<ReactionRules, Error> {
+    match rules_file {
+        Some(rules_file) => {
+            let rules: String = FileReader::new().read_from_file(rules_file)?;
+            rules
+                .parse()
+                .context(ErrorKind::Parse, "Error parsing reaction rules")
         }
-    };
+        None => Ok(ReactionRules::case_pairs()),
+    }
+}
 
-    let input: String = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
+/// Returns the unit type whose removal results in the shortest polymer, together with the
+/// length of that polymer.
+fn find_shortest_polymer(rules: &ReactionRules, input: &str) -> (String, usize) {
+    rank_removals(rules, input, 1)
+        .into_iter()
+        .next()
+        .map(|removal| (removal.unit_types[0].clone(), removal.units))
+        .unwrap_or_default()
+}
+
+/// Rules determining which units react (and are destroyed) when adjacent.
+///
+/// Units are the declared multi-character tokens (matching the longest token first) and
+/// otherwise single grapheme clusters. Rules can be parsed from a file containing one rule per
+/// line (empty lines and lines starting with `#` are ignored):
+/// ```text
+/// case        units react with units that only differ in case
+/// unit ab     "ab" is a single unit
+/// x y         units "x" and "y" react (in either order); both are units
+/// ```
+#[derive(Debug, Default)]
+struct ReactionRules {
+    /// Whether units react with units that only differ in case (using Unicode case folding).
+    case_pairs: bool,
+    /// Pairs of units that react, in both orders.
+    pairs: HashSet<(String, String)>,
+    /// Multi-character units, longest first.
+    tokens: Vec<String>,
+}
+
+/// Result of fully reacting a polymer.
+#[derive(Debug, PartialEq)]
+struct Reduction {
+    /// The remaining polymer.
+    polymer: String,
+    /// Number of units in the remaining polymer.
+    units: usize,
+    /// Number of reactions each unit type took part in.
+    reactions: BTreeMap<String, usize>,
+}
+
+impl ReactionRules {
+    /// Rules of the puzzle: units react with the same unit of opposite case.
+    fn case_pairs() -> Self {
+        Self {
+            case_pairs: true,
+            ..Self::default()
         }
-    };
+    }
 
-    // Check ASCII
-    if !input.is_ascii() {
-        println!("Input is not ASCII!");
-        std::process::exit(1);
+    /// Declares `token` to be a single unit.
+    fn add_token(&mut self, token: &str) {
+        if graphemes(token).nth(1).is_some() && !self.tokens.iter().any(|t| t == token) {
+            self.tokens.push(token.to_string());
+            self.tokens.sort_by_key(|t| std::cmp::Reverse(t.len()));
+        }
     }
 
-    println!("Remaining units: {}", react(&input, None));
+    /// Lets units `a` and `b` react with each other.
+    fn add_pair(&mut self, a: &str, b: &str) {
+        self.add_token(a);
+        self.add_token(b);
+        self.pairs.insert((a.to_string(), b.to_string()));
+        self.pairs.insert((b.to_string(), a.to_string()));
+    }
 
-    let (problematic_unit, shortest_polymer) = find_shortest_polymer(&input);
+    /// Splits `polymer` into units.
+    fn tokenize<'a>(&self, polymer: &'a str) -> Vec<&'a str> {
+        let mut units = Vec::new();
+        let mut rest = polymer;
+        while !rest.is_empty() {
+            let unit = match self.tokens.iter().find(|t| rest.starts_with(t.as_str())) {
+                Some(token) => &rest[..token.len()],
+                None => graphemes(rest).next().unwrap(),
+            };
+            units.push(unit);
+            rest = &rest[unit.len()..];
+        }
+        units
+    }
 
-    println!(
-        "Shortest polymer: Remove {} => Resulting length: {}",
-        problematic_unit as char, shortest_polymer
-    );
+    /// Returns the type of `unit`: its case folded form if units of opposite case react,
+    /// otherwise the unit itself.
+    fn unit_type(&self, unit: &str) -> String {
+        if self.case_pairs {
+            unit.to_lowercase()
+        } else {
+            unit.to_string()
+        }
+    }
+
+    /// Returns whether units `a` and `b` react.
+    fn reacts(&self, a: &str, b: &str) -> bool {
+        (self.case_pairs && a != b && a.to_lowercase() == b.to_lowercase())
+            || self.pairs.contains(&(a.to_string(), b.to_string()))
+    }
+
+    /// Reacts `polymer` until no more reactions are possible, after removing all units of type
+    /// `remove`.
+    fn reduce(&self, polymer: &str, remove: Option<&str>) -> Reduction {
+        let remove: Vec<&str> = remove.into_iter().collect();
+        self.prepare(polymer).reduce(&remove)
+    }
+
+    /// Splits `polymer` into units and determines which of them react, for repeated reductions.
+    fn prepare<'a>(&self, polymer: &'a str) -> Polymer<'a> {
+        // Identify each distinct unit by an index
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let mut sequence = Vec::new();
+        for unit in self.tokenize(polymer) {
+            let next_id = ids.len();
+            sequence.push(*ids.entry(unit).or_insert(next_id));
+        }
+        let mut units = vec![""; ids.len()];
+        for (&unit, &id) in ids.iter() {
+            units[id] = unit;
+        }
+
+        let types = units.iter().map(|unit| self.unit_type(unit)).collect();
+        let mut reacting = HashSet::new();
+        for (a, unit_a) in units.iter().enumerate() {
+            for (b, unit_b) in units.iter().enumerate() {
+                if self.reacts(unit_a, unit_b) {
+                    reacting.insert((a, b));
+                }
+            }
+        }
+
+        Polymer {
+            units,
+            types,
+            sequence,
+            reacting,
+        }
+    }
 }
 
-fn find_shortest_polymer(input: &str) -> (u8, usize) {
-    (b'a'..=b'z')
-        .map(|c| (c, react(&input, Some(c))))
-        .min_by_key(|(_, length)| *length)
-        .unwrap_or_default()
+/// Polymer split into units, each identified by an index into `units`.
+#[derive(Debug, Clone)]
+struct Polymer<'a> {
+    /// Distinct units of the polymer.
+    units: Vec<&'a str>,
+    /// Type of each unit.
+    types: Vec<String>,
+    /// Units of the polymer, in order.
+    sequence: Vec<usize>,
+    /// Pairs of units that react.
+    reacting: HashSet<(usize, usize)>,
 }
 
-fn react(input: &str, ignore: Option<u8>) -> usize {
-    let mut stack = VecDeque::new();
+impl<'a> Polymer<'a> {
+    /// Reacts the polymer until no more reactions are possible, after removing all units whose
+    /// type is contained in `remove`.
+    fn reduce(&self, remove: &[&str]) -> Reduction {
+        let mut reactions: BTreeMap<String, usize> = BTreeMap::new();
+        let remaining = self.react(remove, |a, b| {
+            *reactions.entry(self.types[a].clone()).or_default() += 1;
+            if self.types[b] != self.types[a] {
+                *reactions.entry(self.types[b].clone()).or_default() += 1;
+            }
+        });
+
+        Reduction {
+            polymer: remaining.iter().map(|&id| self.units[id]).collect(),
+            units: remaining.len(),
+            reactions,
+        }
+    }
 
-    let ignore = match ignore {
-        Some(ignore) => ignore & !32,
-        None => 0,
-    };
+    /// Returns the fully reacted polymer.
+    fn reduced(&self) -> Self {
+        Self {
+            sequence: self.react(&[], |_, _| {}),
+            ..self.clone()
+        }
+    }
 
-    for c in input.bytes() {
-        if ignore > 0 && (c & !32) == ignore {
-            continue;
+    /// Returns whether removing a unit type from the reduced polymer yields the same result as
+    /// removing it from the original polymer.
+    ///
+    /// This holds if every unit reacts with at most one other unit, which is of the same type:
+    /// then reacting behaves like cancelling inverses, so the result doesn't depend on the order
+    /// of the reactions, and removing a type removes both partners of every reaction involving
+    /// that type.
+    fn removal_commutes(&self) -> bool {
+        let mut partners = vec![0; self.units.len()];
+        for &(a, b) in self.reacting.iter() {
+            if self.types[a] != self.types[b] {
+                return false;
+            }
+            partners[a] += 1;
         }
+        partners.iter().all(|&count| count <= 1)
+    }
 
-        match stack.back() {
-            Some(last) => {
-                if last ^ c == 32 {
-                    stack.pop_back();
-                } else {
-                    stack.push_back(c);
+    /// Reacts the polymer, skipping units whose type is contained in `remove`, calls
+    /// `on_reaction` for every pair of reacting units and returns the remaining units.
+    fn react<F: FnMut(usize, usize)>(&self, remove: &[&str], mut on_reaction: F) -> Vec<usize> {
+        let removed: Vec<bool> = self
+            .types
+            .iter()
+            .map(|unit_type| remove.contains(&unit_type.as_str()))
+            .collect();
+
+        let mut stack: Vec<usize> = Vec::new();
+        for &id in self.sequence.iter() {
+            if removed[id] {
+                continue;
+            }
+
+            match stack.last() {
+                Some(&last) if self.reacting.contains(&(last, id)) => {
+                    stack.pop();
+                    on_reaction(last, id);
                 }
+                _ => stack.push(id),
+            }
+        }
+        stack
+    }
+}
+
+/// Removal of a set of unit types from a polymer.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Removal {
+    /// Number of units remaining after fully reacting the polymer.
+    units: usize,
+    /// The removed unit types, sorted.
+    unit_types: Vec<String>,
+}
+
+/// Tries removing every combination of up to `max_types` unit types (at least one) from `input`,
+/// and returns the removals ranked by the length of the resulting polymer (shortest first).
+///
+/// If possible, the polymer is reduced once up front and the removals are applied to the
+/// reduced polymer, which is usually much shorter. The combinations are tried in parallel.
+fn rank_removals(rules: &ReactionRules, input: &str, max_types: usize) -> Vec<Removal> {
+    let polymer = rules.prepare(input);
+    // The unit types of the input, as reducing may destroy all units of a type
+    let mut unit_types: Vec<String> = polymer.types.clone();
+    unit_types.sort_unstable();
+    unit_types.dedup();
+    let unit_types: Vec<&str> = unit_types.iter().map(String::as_str).collect();
+
+    let polymer = if polymer.removal_commutes() {
+        polymer.reduced()
+    } else {
+        polymer
+    };
+    let candidates: Vec<Vec<&str>> = (1..=max_types.min(unit_types.len()))
+        .flat_map(|k| combinations(unit_types.len(), k))
+        .map(|indices| indices.iter().map(|&i| unit_types[i]).collect())
+        .collect();
+
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(1);
+    let mut removals: Vec<Removal> = thread::scope(|scope| {
+        let workers: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let polymer = &polymer;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|remove| Removal {
+                            units: polymer.react(remove, |_, _| {}).len(),
+                            unit_types: remove.iter().map(|t| t.to_string()).collect(),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    removals.sort_unstable();
+    removals
+}
+
+/// Returns all `k`-element subsets of `0..n` (as sorted indices), in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    if k > n {
+        return result;
+    }
+
+    let mut indices: Vec<usize> = (0..k).collect();
+    loop {
+        result.push(indices.clone());
+        // Advance the rightmost index that can still be incremented
+        let i = match (0..k).rev().find(|&i| indices[i] < n - k + i) {
+            Some(i) => i,
+            None => return result,
+        };
+        indices[i] += 1;
+        for j in i + 1..k {
+            indices[j] = indices[j - 1] + 1;
+        }
+    }
+}
+
+impl FromStr for ReactionRules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = ReactionRules::default();
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                ["case"] => rules.case_pairs = true,
+                ["unit", token] => rules.add_token(token),
+                [a, b] => rules.add_pair(a, b),
+                _ => return Err(format!("Invalid rule: {}", line)),
             }
-            None => stack.push_back(c),
         }
+        Ok(rules)
     }
+}
 
-    stack.len()
+/// Generates a polymer consisting of `size` units.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut polymer: String = (0..size)
+        .map(|_| {
+            let unit = b'a' + rng.range_usize(0, 26) as u8;
+            if rng.chance(0.5) {
+                unit.to_ascii_uppercase() as char
+            } else {
+                unit as char
+            }
+        })
+        .collect();
+    polymer.push('\n');
+    polymer
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn react(input: &str, ignore: Option<&str>) -> usize {
+        ReactionRules::case_pairs().reduce(input, ignore).units
+    }
+
     #[test]
     fn test_reaction() {
         assert_eq!(0, react("aA", None));
@@ -86,25 +465,185 @@ mod tests {
 
     #[test]
     fn test_reaction_with_ignore() {
-        assert_eq!(6, react("dabAcCaCBAcCcaDA", Some(b'a')));
-        assert_eq!(8, react("dabAcCaCBAcCcaDA", Some(b'b')));
-        assert_eq!(4, react("dabAcCaCBAcCcaDA", Some(b'c')));
-        assert_eq!(6, react("dabAcCaCBAcCcaDA", Some(b'd')));
+        assert_eq!(6, react("dabAcCaCBAcCcaDA", Some("a")));
+        assert_eq!(8, react("dabAcCaCBAcCcaDA", Some("b")));
+        assert_eq!(4, react("dabAcCaCBAcCcaDA", Some("c")));
+        assert_eq!(6, react("dabAcCaCBAcCcaDA", Some("d")));
+    }
+
+    #[test]
+    fn test_reduction() {
+        let reduction = ReactionRules::case_pairs().reduce("dabAcCaCBAcCcaDA", None);
+        assert_eq!("dabCBAcaDA", reduction.polymer);
+        assert_eq!(10, reduction.units);
+        let reactions: Vec<(&str, usize)> = reduction
+            .reactions
+            .iter()
+            .map(|(unit_type, &count)| (unit_type.as_str(), count))
+            .collect();
+        assert_eq!(vec![("a", 1), ("c", 2)], reactions);
+
+        assert_eq!(
+            ("c".to_string(), 4),
+            find_shortest_polymer(&ReactionRules::case_pairs(), "dabAcCaCBAcCcaDA")
+        );
+    }
+
+    #[test]
+    fn test_unicode_case_folding() {
+        let rules = ReactionRules::case_pairs();
+        assert_eq!(0, react("éÉ", None));
+        assert_eq!(0, react("ΣσΔδ", None));
+        assert_eq!(2, react("éE", None));
+        // Combining marks belong to their base character
+        assert_eq!(0, react("e\u{301}E\u{301}", None));
+        assert_eq!(2, rules.tokenize("e\u{301}E").len());
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules: ReactionRules = "\
+# Custom rules
+ab x
+unit cde
+
+y z
+"
+        .parse()
+        .unwrap();
+        assert_eq!(vec!["ab", "x", "cde", "c", "a"], rules.tokenize("abxcdeca"));
+
+        let reduction = rules.reduce("yabxzcdeaBzy", None);
+        assert_eq!("cdeaB", reduction.polymer);
+        assert_eq!(3, reduction.units);
+        let reactions: Vec<(&str, usize)> = reduction
+            .reactions
+            .iter()
+            .map(|(unit_type, &count)| (unit_type.as_str(), count))
+            .collect();
+        assert_eq!(vec![("ab", 1), ("x", 1), ("y", 2), ("z", 2)], reactions);
+        assert_eq!(6, rules.reduce("yabxzcdeaBzy", Some("ab")).units);
+
+        let rules: ReactionRules = "case\nab x".parse().unwrap();
+        assert_eq!(0, rules.reduce("aAabxBb", None).units);
+
+        assert!("a b c".parse::<ReactionRules>().is_err());
+        assert!("unit".parse::<ReactionRules>().is_err());
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(vec![vec![0, 1], vec![0, 2], vec![1, 2]], combinations(3, 2));
+        assert_eq!(vec![Vec::<usize>::new()], combinations(3, 0));
+        assert!(combinations(2, 3).is_empty());
+        assert_eq!(120, combinations(10, 3).len());
+    }
+
+    #[test]
+    fn test_rank_removals() {
+        let rules = ReactionRules::case_pairs();
+        let ranked: Vec<(usize, String)> = rank_removals(&rules, "dabAcCaCBAcCcaDA", 1)
+            .into_iter()
+            .map(|removal| (removal.units, removal.unit_types.join("")))
+            .collect();
+        assert_eq!(
+            vec![
+                (4, "c".to_string()),
+                (6, "a".to_string()),
+                (6, "d".to_string()),
+                (8, "b".to_string())
+            ],
+            ranked
+        );
+
+        // Removals applied to the reduced polymer match removals from the original polymer
+        let input = generate(&mut Rng::new(41), 2000);
+        let input = input.trim();
+        assert!(rules.prepare(input).removal_commutes());
+        let ranked = rank_removals(&rules, input, 2);
+        assert_eq!(26 + 26 * 25 / 2, ranked.len());
+        for removal in ranked.iter().step_by(17) {
+            let remove: Vec<&str> = removal.unit_types.iter().map(String::as_str).collect();
+            assert_eq!(
+                rules.prepare(input).reduce(&remove).units,
+                removal.units,
+                "{:?}",
+                remove
+            );
+        }
+        assert!(ranked.windows(2).all(|w| w[0].units <= w[1].units));
+    }
+
+    #[test]
+    fn test_rank_removals_without_reuse() {
+        // Removing "a" from the reduced polymer "" would leave "" instead of "x"
+        let rules: ReactionRules = "a x".parse().unwrap();
+        assert!(!rules.prepare("ax").removal_commutes());
+        let ranked = rank_removals(&rules, "ax", 2);
+        assert_eq!(
+            vec![
+                Removal {
+                    units: 0,
+                    unit_types: vec!["a".to_string(), "x".to_string()]
+                },
+                Removal {
+                    units: 1,
+                    unit_types: vec!["a".to_string()]
+                },
+                Removal {
+                    units: 1,
+                    unit_types: vec!["x".to_string()]
+                },
+            ],
+            ranked
+        );
+
+        // Every unit type is tried, even if the polymer reduces to nothing
+        let rules = ReactionRules::case_pairs();
+        assert!(rules.prepare("aA").removal_commutes());
+        assert_eq!(
+            vec![Removal {
+                units: 0,
+                unit_types: vec!["a".to_string()]
+            }],
+            rank_removals(&rules, "aA", 2)
+        );
+        assert_eq!(("a".to_string(), 0), find_shortest_polymer(&rules, "aA"));
+
+        // Kelvin sign, "K" and "k" all react with each other
+        let rules = ReactionRules::case_pairs();
+        assert!(!rules.prepare("\u{212a}Kk").removal_commutes());
+        assert!(rules.prepare("Kk").removal_commutes());
     }
 
     #[test]
     fn test_part1() {
         let input: String = FileReader::new().read_from_file("input.txt").unwrap();
-        assert!(input.is_ascii());
-        assert_eq!(11546, react(&input, None));
+        assert_eq!(11546, react(input.trim(), None));
     }
 
     #[test]
     fn test_part2() {
         let input: String = FileReader::new().read_from_file("input.txt").unwrap();
-        assert!(input.is_ascii());
 
-        let (_, shortest_polymer) = find_shortest_polymer(&input);
+        let (_, shortest_polymer) =
+            find_shortest_polymer(&ReactionRules::case_pairs(), input.trim());
         assert_eq!(5124, shortest_polymer);
     }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(&mut Rng::new(1), 1000);
+        assert_eq!(1000, input.trim_end().len());
+        assert!(react(input.trim_end(), None) <= 1000);
+    }
+
+    #[test]
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(5, solve, generate, size, seed).unwrap();
+            }
+        }
+    }
 }
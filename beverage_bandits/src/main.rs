@@ -1,33 +1,25 @@
 use std::collections::VecDeque;
-use std::env;
 use std::{thread, time};
 
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(15, solve);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
     let visualize = false;
     let delay = time::Duration::from_millis(100);
 
     for power in 3.. {
-        let mut combat = Combat::create(&input, power);
+        let mut combat =
+            Combat::create(&input, power).context(ErrorKind::Parse, "Error reading map")?;
         if visualize {
-            println!("{}", combat);
+            output.diagnostic(&combat);
             thread::sleep(delay);
         }
         loop {
@@ -36,8 +28,8 @@ fn main() {
                 break;
             }
             if visualize {
-                println!("After round {}", combat.completed_rounds);
-                println!("{}", combat);
+                output.diagnostic(format!("After round {}", combat.completed_rounds));
+                output.diagnostic(&combat);
                 thread::sleep(delay);
             }
         }
@@ -45,22 +37,31 @@ fn main() {
         let completed_round = combat.completed_rounds;
         let killed_elves = combat.killed_elves;
         let sum_of_hp = combat.calculate_sum_of_hit_points();
-        println!(
+        let outcome = completed_round * sum_of_hp;
+        let summary = format!(
             "Elf attack power: {}, Completed rounds: {}, Killed elves: {}, Sum of hit points: {} => {}",
             power,
             completed_round,
             killed_elves,
             sum_of_hp,
-            completed_round * sum_of_hp
+            outcome
         );
+        if power == 3 {
+            output.answer(1, outcome, &summary);
+        } else if killed_elves != 0 {
+            output.diagnostic(&summary);
+        }
         if visualize {
             thread::sleep(delay * 10);
         }
 
         if killed_elves == 0 {
+            output.answer(2, outcome, &summary);
             break;
         }
     }
+
+    Ok(())
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -76,12 +77,12 @@ struct Combat {
 }
 
 impl Combat {
-    fn create(input: &[String], attack_power_elves: usize) -> Self {
-        Self {
-            grid: Grid::create(&input, attack_power_elves),
+    fn create(input: &[String], attack_power_elves: usize) -> Result<Self, String> {
+        Ok(Self {
+            grid: Grid::create(&input, attack_power_elves)?,
             completed_rounds: 0,
             killed_elves: 0,
-        }
+        })
     }
 
     fn fight_round(&mut self) -> CombatState {
@@ -434,7 +435,7 @@ struct Grid {
 }
 
 impl Grid {
-    fn create(input: &[String], attack_power_elves: usize) -> Self {
+    fn create(input: &[String], attack_power_elves: usize) -> Result<Self, String> {
         let (width, height) = if !input.is_empty() {
             (input[0].len(), input.len())
         } else {
@@ -446,7 +447,7 @@ impl Grid {
         let mut y = 0;
         let mut unit_id = 0;
 
-        input.iter().flat_map(|s| s.chars()).for_each(|c| {
+        for c in input.iter().flat_map(|s| s.chars()) {
             let cell = match c {
                 '.' => Cell::Open,
                 '#' => Cell::Wall,
@@ -465,7 +466,7 @@ impl Grid {
                     unit_id += 1;
                     Cell::Unit(Unit::new(unit_id - 1, UnitType::Goblin, x, y, 200, 3))
                 }
-                _ => panic!("unexpected input!"),
+                c => return Err(format!("unexpected input: {}", c)),
             };
             grid.push(cell);
 
@@ -474,13 +475,13 @@ impl Grid {
                 x = 0;
                 y += 1;
             }
-        });
+        }
 
-        Self {
+        Ok(Self {
             width,
             height,
             grid,
-        }
+        })
     }
 
     fn at(&self, pos: &GridPosition) -> Cell {
@@ -633,7 +634,7 @@ mod tests {
             "#.G.E.#".to_string(),
             "#######".to_string(),
         ];
-        let combat = Combat::create(&input, 3);
+        let combat = Combat::create(&input, 3).unwrap();
         let mut order = combat.combat_order().into_iter();
         assert_eq!(
             Some(Unit::new(0, UnitType::Goblin, 2, 1, 200, 3)),
@@ -682,7 +683,7 @@ mod tests {
             "#.G.#G#".to_string(),
             "#######".to_string(),
         ];
-        let combat = Combat::create(&input, 3);
+        let combat = Combat::create(&input, 3).unwrap();
         let shortest_paths = find_shortest_paths(
             &combat.grid,
             GridPosition { x: 1, y: 1 },
@@ -1,36 +1,31 @@
-use std::env;
-
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(14, solve);
+}
 
-    let input: Vec<usize> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<usize> = FileReader::new().read_from_file(input_file)?;
 
     if input.len() != 1 {
-        println!("Expected single input!");
-        std::process::exit(1);
+        return Err(Error::parse("Expected single input!"));
     }
     let input = input[0];
-    println!("Input: {}", input);
+    output.diagnostic(format!("Input: {}", input));
 
     let score = make_recipes_part1([3, 7], input, 10);
-    println!("Score: {}", score);
+    output.answer(1, &score, format!("Score: {}", score));
 
     let first_appearance = make_recipes_part2([3, 7], input);
-    println!("First appearance: {}", first_appearance);
+    output.answer(
+        2,
+        first_appearance,
+        format!("First appearance: {}", first_appearance),
+    );
+
+    Ok(())
 }
 
 fn make_recipes_part1(
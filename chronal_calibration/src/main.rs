@@ -1,91 +1,354 @@
-use std::collections::HashSet;
-use std::env;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
-use util::input::{FileReader, FromFile};
+use util::cli::{self, Format, Output, SubcommandArgs};
+use util::error::Error;
+use util::input::{self, parse_lines, FileReader, FromFile};
+
+/// Number of changes between two checkpoints while calibrating.
+const CHECKPOINT_INTERVAL: u64 = 1_000_000;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run_with_subcommands(1, solve, None, &[("repeats", repeats_command)]);
+}
+
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let calibration = calibrate(
+        BufReader::new(File::open(input_file)?),
+        CHECKPOINT_INTERVAL,
+        |checkpoint| output.diagnostic(format!("Checkpoint: {}", checkpoint)),
+    )?;
+    output.diagnostic(format!("Calibrated: {}", calibration));
+    output.answer(
+        1,
+        calibration.frequency,
+        format!("Resulting frequency: {}", calibration.frequency),
+    );
+
+    // Unlike the resulting frequency, finding repeats requires all changes at once
+    let input: Vec<i64> = FileReader::new().read_from_file(input_file)?;
+
+    match first_frequency_reached_twice(&input) {
+        Some(repeat) => output.answer(
+            2,
+            repeat.frequency,
+            format!(
+                "First frequency reached twice: {} (in iteration {})",
+                repeat.frequency, repeat.iteration
+            ),
+        ),
+        None => output.diagnostic("No frequency is ever reached twice!"),
+    }
+
+    Ok(())
+}
+
+/// Prints how the frequency drifts with each pass over the changes, and in which pass the first
+/// frequency reached twice is reached again, as diagnostics.
+///
+/// Usage: `repeats <input file> [--format text|json] [--diagnostics]`.
+fn repeats_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &["diagnostics"], &["format"])?;
+    let format = args.value::<Format>("format")?.unwrap_or(Format::Text);
+    let output = Output::new(1, format, args.flag("diagnostics"));
+    let input: Vec<i64> = FileReader::new().read_from_file(args.input_file())?;
+
+    output.diagnostic(format!(
+        "Drift per pass: {}",
+        input.iter().map(|&f| i128::from(f)).sum::<i128>()
+    ));
+    match first_frequency_reached_twice(&input) {
+        Some(repeat) => output.diagnostic(format!(
+            "First frequency reached twice: {} (in iteration {})",
+            repeat.frequency, repeat.iteration
+        )),
+        None => output.diagnostic("No frequency is ever reached twice!"),
+    }
+
+    Ok(())
+}
 
-    let input: Vec<i64> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
+/// State of a running calibration.
+#[derive(Debug, Clone, PartialEq)]
+struct Calibration {
+    /// Current frequency.
+    frequency: i64,
+    /// Lowest frequency reached so far (including the initial 0).
+    min: i64,
+    /// Highest frequency reached so far (including the initial 0).
+    max: i64,
+    /// Number of changes applied so far.
+    changes: u64,
+}
+
+impl Calibration {
+    fn new() -> Self {
+        Self {
+            frequency: 0,
+            min: 0,
+            max: 0,
+            changes: 0,
         }
-    };
+    }
 
-    let sum: i64 = resulting_frequency(&input);
-    println!("Resulting frequency: {}", sum);
+    fn apply(&mut self, change: i64) {
+        self.frequency += change;
+        self.min = self.min.min(self.frequency);
+        self.max = self.max.max(self.frequency);
+        self.changes += 1;
+    }
+}
+
+impl std::fmt::Display for Calibration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "frequency {} after {} changes (min: {}, max: {})",
+            self.frequency, self.changes, self.min, self.max
+        )
+    }
+}
 
-    let first_freq = first_frequency_reached_twice(&input);
-    println!("First frequency reached twice: {}", first_freq);
+/// Applies the stream of changes read from `reader` (one per line), using constant memory.
+///
+/// `checkpoint` is called with the running calibration every `checkpoint_interval` changes.
+/// Errors for lines that can't be parsed name the line number.
+fn calibrate<R, F>(
+    reader: R,
+    checkpoint_interval: u64,
+    mut checkpoint: F,
+) -> Result<Calibration, Error>
+where
+    R: BufRead,
+    F: FnMut(&Calibration),
+{
+    let mut calibration = Calibration::new();
+    for (i, change) in parse_lines(reader).enumerate() {
+        let change = change.map_err(|error| match error {
+            input::Error::ParseError(e) => {
+                Error::parse(format!("Error parsing line {}", i + 1)).with_source(e)
+            }
+            error => Error::from(error),
+        })?;
+        calibration.apply(change);
+        if calibration.changes.is_multiple_of(checkpoint_interval) {
+            checkpoint(&calibration);
+        }
+    }
+    Ok(calibration)
 }
 
-fn resulting_frequency(frequencies: &[i64]) -> i64 {
-    frequencies.iter().sum()
+#[derive(Debug, PartialEq)]
+struct Repeat {
+    /// Frequency that is reached twice.
+    frequency: i64,
+    /// Pass over the list of changes (starting at 1) during which the frequency is reached
+    /// the second time.
+    iteration: u64,
 }
 
-fn first_frequency_reached_twice(frequencies: &[i64]) -> i64 {
-    let mut intermediates = HashSet::new();
-    intermediates.insert(0);
-    let mut result = 0;
-    let mut sum = 0;
+impl Repeat {
+    /// Create new `Repeat` for the frequency reached after `step` changes in a list of `n`.
+    fn at_step(frequency: i64, step: u128, n: u128) -> Self {
+        Self {
+            frequency,
+            iteration: step.div_ceil(n) as u64,
+        }
+    }
+}
 
-    for f in frequencies.iter().cycle() {
+/// Finds the first frequency reached twice while repeatedly applying the list of changes.
+///
+/// After `t` passes, the frequency reached after the `i`-th change is `prefix[i] + t * drift`, so
+/// two prefix sums can only ever meet if they are congruent modulo the drift (the sum of all
+/// changes). Within each residue class, every prefix sum is caught up by its nearest neighbour
+/// from behind, which only requires sorting the prefix sums: O(n log n) instead of simulating
+/// an unbounded number of passes.
+///
+/// Returns `None` if no frequency is ever reached twice.
+fn first_frequency_reached_twice(frequencies: &[i64]) -> Option<Repeat> {
+    let n = frequencies.len() as u128;
+    if n == 0 {
+        return None;
+    }
+
+    // Frequencies reached during the first pass, starting with the initial 0.
+    // Step `k` (k changes applied) reaches prefix[k % n] + (k / n) * drift.
+    let mut prefix = Vec::with_capacity(frequencies.len());
+    let mut sum = 0;
+    let mut seen = HashSet::new();
+    for (i, f) in frequencies.iter().enumerate() {
+        if !seen.insert(sum) {
+            return Some(Repeat::at_step(sum, i as u128, n));
+        }
+        prefix.push(sum);
         sum += f;
-        if !intermediates.insert(sum) {
-            result = sum;
-            break;
+    }
+    let drift = sum;
+
+    if drift == 0 {
+        // Everything repeats with the next pass, starting with the initial frequency
+        return Some(Repeat::at_step(0, n, n));
+    }
+
+    let mut residue_classes: HashMap<i64, Vec<(i64, usize)>> = HashMap::new();
+    for (i, &p) in prefix.iter().enumerate() {
+        residue_classes
+            .entry(p.rem_euclid(drift))
+            .or_default()
+            .push((p, i));
+    }
+
+    let mut first: Option<(u128, i64)> = None;
+    for class in residue_classes.values_mut() {
+        class.sort_unstable();
+        for pair in class.windows(2) {
+            // The frequency further behind (in direction of the drift) catches up with the other
+            let ((behind, i), (ahead, _)) = if drift > 0 {
+                (pair[0], pair[1])
+            } else {
+                (pair[1], pair[0])
+            };
+            let passes = ((ahead - behind) / drift) as u128;
+            let step = passes * n + i as u128;
+            let is_first = match first {
+                Some((first_step, _)) => step < first_step,
+                None => true,
+            };
+            if is_first {
+                first = Some((step, ahead));
+            }
         }
     }
-    result
+
+    first.map(|(step, frequency)| Repeat::at_step(frequency, step, n))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use util::error::ErrorKind;
+
+    #[test]
+    fn test_calibrate() {
+        let input = std::io::Cursor::new("+1\n-3\n+5\n-1\n+2\n");
+        let mut checkpoints = Vec::new();
+        let calibration = calibrate(input, 2, |c| checkpoints.push(c.frequency)).unwrap();
+        assert_eq!(
+            Calibration {
+                frequency: 4,
+                min: -2,
+                max: 4,
+                changes: 5,
+            },
+            calibration
+        );
+        assert_eq!(vec![-2, 2], checkpoints);
+
+        let error = calibrate(std::io::Cursor::new("+1\n1x\n"), 2, |_| ()).unwrap_err();
+        assert_eq!(ErrorKind::Parse, error.kind());
+        assert_eq!("Error parsing line 2", error.to_string());
+    }
 
     #[test]
     fn test_first_frequency_reached_twice_1() {
         let f = [1, -1];
-        assert_eq!(0, first_frequency_reached_twice(&f));
+        assert_eq!(0, first_frequency_reached_twice(&f).unwrap().frequency);
     }
 
     #[test]
     fn test_first_frequency_reached_twice_2() {
         let f = [3, 3, 4, -2, -4];
-        assert_eq!(10, first_frequency_reached_twice(&f));
+        assert_eq!(10, first_frequency_reached_twice(&f).unwrap().frequency);
     }
 
     #[test]
     fn test_first_frequency_reached_twice_3() {
         let f = [-6, 3, 8, 5, -6];
-        assert_eq!(5, first_frequency_reached_twice(&f));
+        assert_eq!(5, first_frequency_reached_twice(&f).unwrap().frequency);
     }
 
     #[test]
     fn test_first_frequency_reached_twice_4() {
         let f = [7, 7, -2, -7, -4];
-        assert_eq!(14, first_frequency_reached_twice(&f));
+        assert_eq!(14, first_frequency_reached_twice(&f).unwrap().frequency);
+    }
+
+    #[test]
+    fn test_first_frequency_iteration() {
+        assert_eq!(
+            Some(Repeat {
+                frequency: 10,
+                iteration: 2,
+            }),
+            first_frequency_reached_twice(&[3, 3, 4, -2, -4])
+        );
+        assert_eq!(
+            Some(Repeat {
+                frequency: 1,
+                iteration: 1,
+            }),
+            first_frequency_reached_twice(&[1, 1, -1, 1])
+        );
+        assert_eq!(
+            Some(Repeat {
+                frequency: 0,
+                iteration: 1,
+            }),
+            first_frequency_reached_twice(&[2, -1, -2, 1])
+        );
+        assert_eq!(
+            Some(Repeat {
+                frequency: -1_000_000,
+                iteration: 1_000_000,
+            }),
+            first_frequency_reached_twice(&[-1_000_000, 999_999])
+        );
+    }
+
+    #[test]
+    fn test_never_repeats() {
+        assert_eq!(None, first_frequency_reached_twice(&[]));
+        assert_eq!(None, first_frequency_reached_twice(&[1, 1]));
+        assert_eq!(None, first_frequency_reached_twice(&[5, -2, 4]));
+    }
+
+    #[test]
+    fn test_matches_simulation() {
+        fn simulate(frequencies: &[i64]) -> Repeat {
+            let mut seen = HashSet::new();
+            let mut sum = 0;
+            for (step, f) in frequencies.iter().cycle().enumerate() {
+                if !seen.insert(sum) {
+                    return Repeat::at_step(sum, step as u128, frequencies.len() as u128);
+                }
+                sum += f;
+            }
+            unreachable!()
+        }
+
+        let mut rng = util::random::Rng::new(1);
+        for _ in 0..1000 {
+            let n = rng.range_usize(1, 10);
+            let frequencies: Vec<i64> = (0..n).map(|_| rng.range(-20, 20)).collect();
+            if let Some(repeat) = first_frequency_reached_twice(&frequencies) {
+                assert_eq!(simulate(&frequencies), repeat, "{:?}", frequencies);
+            }
+        }
     }
 
     #[test]
     fn test_part_1() {
-        let input: Vec<i64> = FileReader::new().read_from_file("input.txt").unwrap();
-        let sum: i64 = resulting_frequency(&input);
-        assert_eq!(580, sum);
+        let input = BufReader::new(File::open("input.txt").unwrap());
+        let calibration = calibrate(input, CHECKPOINT_INTERVAL, |_| ()).unwrap();
+        assert_eq!(580, calibration.frequency);
     }
 
     #[test]
     fn test_part_2() {
         let input: Vec<i64> = FileReader::new().read_from_file("input.txt").unwrap();
         let first_freq = first_frequency_reached_twice(&input);
-        assert_eq!(81972, first_freq);
+        assert_eq!(81972, first_freq.unwrap().frequency);
     }
 }
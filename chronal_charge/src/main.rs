@@ -1,33 +1,19 @@
-use std::env;
-
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(11, solve);
+}
 
-    let input: String = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: String = FileReader::new().read_from_file(input_file)?;
 
-    let grid_serial_number = match input.parse::<usize>() {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+    let grid_serial_number = input
+        .parse::<usize>()
+        .context(ErrorKind::Parse, "Invalid grid serial number")?;
 
-    println!("Grid serial number: {}", grid_serial_number);
+    output.diagnostic(format!("Grid serial number: {}", grid_serial_number));
 
     let width = 300;
     let height = 300;
@@ -42,9 +28,13 @@ fn main() {
     let (mut max_coord_x, mut max_coord_y, mut max_total_power) =
         calculate_max_total_power(&grid, width, height, 3);
 
-    println!(
-        "Total power level at ({},{}) is {}",
-        max_coord_x, max_coord_y, max_total_power
+    output.answer(
+        1,
+        format!("{},{}", max_coord_x, max_coord_y),
+        format!(
+            "Total power level at ({},{}) is {}",
+            max_coord_x, max_coord_y, max_total_power
+        ),
     );
 
     let mut max_size = 3;
@@ -59,10 +49,16 @@ fn main() {
         }
     }
 
-    println!(
-        "Total power level at ({},{},{}) is {}",
-        max_coord_x, max_coord_y, max_size, max_total_power
+    output.answer(
+        2,
+        format!("{},{},{}", max_coord_x, max_coord_y, max_size),
+        format!(
+            "Total power level at ({},{},{}) is {}",
+            max_coord_x, max_coord_y, max_size, max_total_power
+        ),
     );
+
+    Ok(())
 }
 
 fn calculate_max_total_power(
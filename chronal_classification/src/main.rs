@@ -1,28 +1,20 @@
 use std::collections::HashMap;
-use std::env;
 
-use util::input::{FileReader, FromFile};
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
+use util::input::{parse_integers, FileReader, FromFile};
 
 type RegSize = u16;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(16, solve);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
-    let (instruction_samples, test_program) = parse_input(&input);
+    let (instruction_samples, test_program) =
+        parse_input(&input).context(ErrorKind::Parse, "Error parsing input")?;
     let opcodes = [
         Opcode::Addr,
         Opcode::Addi,
@@ -61,9 +53,13 @@ fn main() {
         }
     }
 
-    println!(
-        "Number of samples that behave like three or more opcodes: {}",
-        three_or_more
+    output.answer(
+        1,
+        three_or_more,
+        format!(
+            "Number of samples that behave like three or more opcodes: {}",
+            three_or_more
+        ),
     );
 
     let mut possibilities: [Vec<Opcode>; 16] = Default::default();
@@ -91,7 +87,7 @@ fn main() {
                 if possibilities[opcode as usize].len() == 1 {
                     let found_op = possibilities[opcode as usize][0];
                     if matches.insert(opcode as u8, found_op).is_some() {
-                        panic!("Opcode is not unique!");
+                        return Err(Error::runtime("Opcode is not unique!"));
                     }
 
                     for p in &mut possibilities {
@@ -108,20 +104,26 @@ fn main() {
         }
     }
 
-    println!("Matches:");
+    output.diagnostic("Matches:");
     for (k, v) in matches.iter() {
-        println!("{} => {:?}", k, v);
+        output.diagnostic(format!("{} => {:?}", k, v));
     }
 
     let mut regs = [0; 4];
     for instruction in test_program {
-        evaluate_instruction(
-            &mut regs,
-            matches[&(instruction[0] as u8)],
-            &instruction[1..],
-        );
+        let opcode = matches.get(&(instruction[0] as u8)).context(
+            ErrorKind::Runtime,
+            format!("Could not determine opcode {}", instruction[0]),
+        )?;
+        evaluate_instruction(&mut regs, *opcode, &instruction[1..]);
     }
-    println!("Value contained in register 0: {}", regs[0]);
+    output.answer(
+        2,
+        regs[0],
+        format!("Value contained in register 0: {}", regs[0]),
+    );
+
+    Ok(())
 }
 
 fn matches_opcode(
@@ -205,7 +207,7 @@ enum ParseState {
     Program,
 }
 
-fn parse_input(input: &[String]) -> (Vec<InstructionSample>, Vec<Vec<RegSize>>) {
+fn parse_input(input: &[String]) -> Result<(Vec<InstructionSample>, Vec<Vec<RegSize>>), String> {
     let mut state = ParseState::Before;
     let mut sample = InstructionSample::default();
     let mut samples: Vec<InstructionSample> = Vec::new();
@@ -213,25 +215,17 @@ fn parse_input(input: &[String]) -> (Vec<InstructionSample>, Vec<Vec<RegSize>>)
 
     for line in input {
         if state == ParseState::Before && line.starts_with("Before") {
-            sample.regs_before = parse_registers(line);
-            assert_eq!(4, sample.regs_before.len());
+            sample.regs_before = parse_numbers(line)?;
             state = ParseState::Instruction;
         } else if state == ParseState::Instruction
             && !line.starts_with("Before")
             && !line.starts_with("After")
             && !line.is_empty()
         {
-            sample.instruction = line
-                .split_whitespace()
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.parse::<RegSize>().unwrap())
-                .collect();
-            assert_eq!(4, sample.instruction.len());
+            sample.instruction = parse_numbers(line)?;
             state = ParseState::After;
         } else if state == ParseState::After && line.starts_with("After") {
-            sample.regs_after = parse_registers(line);
-            assert_eq!(4, sample.regs_after.len());
+            sample.regs_after = parse_numbers(line)?;
             state = ParseState::Newline;
         } else if state == ParseState::Newline && line.is_empty() {
             let parsed_sample = std::mem::replace(&mut sample, InstructionSample::default());
@@ -240,30 +234,25 @@ fn parse_input(input: &[String]) -> (Vec<InstructionSample>, Vec<Vec<RegSize>>)
         } else if state != ParseState::Newline && line.is_empty() {
             state = ParseState::Program;
         } else if state == ParseState::Program && !line.is_empty() {
-            let instruction: Vec<RegSize> = line
-                .split_whitespace()
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.parse::<RegSize>().unwrap())
-                .collect();
-            program.push(instruction);
+            program.push(parse_numbers(line)?);
         } else {
-            panic!("Unexpected input!");
+            return Err(format!("Unexpected input: {}", line));
         }
     }
 
-    (samples, program)
+    Ok((samples, program))
 }
 
-fn parse_registers(input: &str) -> Vec<RegSize> {
-    input
-        .split(|c| c == '[' || c == ']')
-        .filter(|s| !s.starts_with("Before") && !s.starts_with("After"))
-        .flat_map(|s| s.split(','))
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<RegSize>().unwrap())
-        .collect()
+fn parse_numbers(line: &str) -> Result<Vec<RegSize>, String> {
+    let numbers: Vec<RegSize> = parse_integers(line).map_err(|e| format!("{}: {}", e, line))?;
+    if numbers.len() != 4 {
+        return Err(format!(
+            "expected 4 numbers, got {}: {}",
+            numbers.len(),
+            line
+        ));
+    }
+    Ok(numbers)
 }
 
 #[cfg(test)]
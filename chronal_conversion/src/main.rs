@@ -1,32 +1,25 @@
 use std::collections::HashMap;
-use std::env;
 
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 type RegSize = u32;
 const REG_NUMBER: usize = 6;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(21, solve);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
-    let program = parse_input(&input);
+    let program = parse_input(&input).context(ErrorKind::Parse, "Error parsing program")?;
 
     let mut regs = [0; REG_NUMBER];
-    program.execute(&mut regs);
+    program.execute(&mut regs, output);
+
+    Ok(())
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -92,7 +85,7 @@ struct Program {
 }
 
 impl Program {
-    fn execute(&self, mut regs: &mut [RegSize]) {
+    fn execute(&self, mut regs: &mut [RegSize], output: &Output) {
         assert_eq!(REG_NUMBER, regs.len());
 
         // From inspection of the input: R0 only occurs in one instruction, namely line 28 (in my input).
@@ -125,9 +118,13 @@ impl Program {
             if ip == 28 {
                 let reg_x = regs[rx as usize];
                 if !first_found {
-                    println!(
-                        "Value of R0 for which program terminates in fewest steps: {}",
-                        reg_x
+                    output.answer(
+                        1,
+                        reg_x,
+                        format!(
+                            "Value of R0 for which program terminates in fewest steps: {}",
+                            reg_x
+                        ),
                     );
                     first_found = true;
                 }
@@ -136,9 +133,13 @@ impl Program {
                     *entry = count;
                     last_unique = reg_x;
                 } else {
-                    println!(
-                        "Value of R0 for which program terminates in most steps: {}",
-                        last_unique
+                    output.answer(
+                        2,
+                        last_unique,
+                        format!(
+                            "Value of R0 for which program terminates in most steps: {}",
+                            last_unique
+                        ),
                     );
                     break;
                 }
@@ -151,7 +152,7 @@ impl Program {
     }
 }
 
-fn parse_input(input: &[String]) -> Program {
+fn parse_input(input: &[String]) -> Result<Program, String> {
     let mut ip = 0;
     let mut got_ip = false;
 
@@ -160,22 +161,24 @@ fn parse_input(input: &[String]) -> Program {
     for line in input {
         if line.starts_with('#') {
             if got_ip {
-                panic!("Already got an IP!");
+                return Err(String::from("Already got an IP!"));
             }
             ip = line
                 .split_whitespace()
-                .skip(1)
-                .map(|s| s.parse::<RegSize>().unwrap())
-                .nth(0)
-                .unwrap();
-            assert!((ip as usize) < REG_NUMBER);
+                .nth(1)
+                .ok_or_else(|| format!("Missing IP register: {}", line))?
+                .parse::<RegSize>()
+                .map_err(|e| format!("{}: {}", e, line))?;
+            if (ip as usize) >= REG_NUMBER {
+                return Err(format!("Invalid IP register: {}", line));
+            }
             got_ip = true;
         } else if line.starts_with(';') {
             // skip comments
             continue;
         } else {
             let mut iter = line.split_whitespace();
-            let opcode = match iter.next().unwrap() {
+            let opcode = match iter.next().unwrap_or("") {
                 "addr" => Opcode::Addr,
                 "addi" => Opcode::Addi,
                 "mulr" => Opcode::Mulr,
@@ -192,18 +195,24 @@ fn parse_input(input: &[String]) -> Program {
                 "eqir" => Opcode::Eqir,
                 "eqri" => Opcode::Eqri,
                 "eqrr" => Opcode::Eqrr,
-                s => panic!("Unknown opcode: {}", s),
+                s => return Err(format!("Unknown opcode: {}", s)),
             };
             let mut args: [RegSize; 3] = [0; 3];
             for arg in args.iter_mut() {
-                *arg = iter.next().unwrap().parse::<RegSize>().unwrap();
+                *arg = iter
+                    .next()
+                    .ok_or_else(|| format!("Missing argument: {}", line))?
+                    .parse::<RegSize>()
+                    .map_err(|e| format!("{}: {}", e, line))?;
+            }
+            if iter.next().is_some() {
+                return Err(format!("Too many arguments: {}", line));
             }
-            assert!(iter.next().is_none());
             instructions.push(Instruction { opcode, args });
         }
     }
 
-    Program { ip, instructions }
+    Ok(Program { ip, instructions })
 }
 
 #[cfg(test)]
@@ -0,0 +1,101 @@
+//! Coordinate parser for day 6, shared by the solver and the fuzz targets.
+
+use adhoc_derive::FromStr;
+
+/// Coordinate on the grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, FromStr)]
+#[adhoc(regex = r"^(?P<x>-?\d+), (?P<y>-?\d+)$")]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan_distance_to(&self, other: &Point) -> u128 {
+        u128::from(self.x.abs_diff(other.x)) + u128::from(self.y.abs_diff(other.y))
+    }
+
+    pub fn chebyshev_distance_to(&self, other: &Point) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Returns the square of the Euclidean distance, which (unlike the distance itself) is exact
+    /// unless it exceeds `u128::MAX`, where it saturates.
+    pub fn squared_euclidean_distance_to(&self, other: &Point) -> u128 {
+        let dx = u128::from(self.x.abs_diff(other.x));
+        let dy = u128::from(self.y.abs_diff(other.y));
+        (dx * dx).saturating_add(dy * dy)
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    #[test]
+    fn test_manhattan_distance() {
+        let p = Point::new(0, 0);
+        let q = Point::new(5, 2);
+        assert_eq!(7, p.manhattan_distance_to(&q));
+        assert_eq!(
+            7,
+            Point::new(-3, 1).manhattan_distance_to(&Point::new(2, -1))
+        );
+    }
+
+    #[test]
+    fn test_other_distances() {
+        let p = Point::new(-3, 1);
+        let q = Point::new(2, -1);
+        assert_eq!(5, p.chebyshev_distance_to(&q));
+        assert_eq!(29, p.squared_euclidean_distance_to(&q));
+        assert_eq!(0, q.squared_euclidean_distance_to(&q));
+
+        let min = Point::new(i64::MIN, i64::MIN);
+        let max = Point::new(i64::MAX, i64::MAX);
+        assert_eq!(2 * u128::from(u64::MAX), min.manhattan_distance_to(&max));
+        assert_eq!(u64::MAX, max.chebyshev_distance_to(&min));
+        assert_eq!(
+            u128::from(u64::MAX) * u128::from(u64::MAX),
+            min.squared_euclidean_distance_to(&Point::new(i64::MAX, i64::MIN))
+        );
+        assert_eq!(u128::MAX, min.squared_euclidean_distance_to(&max));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Point::new(1, 6), "1, 6".parse().unwrap());
+        assert_eq!(Point::new(-1, -6), "-1, -6".parse().unwrap());
+
+        for s in &[
+            "",
+            "1,6",
+            "1, --6",
+            "1, +6",
+            "1, 6, 3",
+            "1, 99999999999999999999",
+        ] {
+            assert!(s.parse::<Point>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(6);
+        for _ in 0..1000 {
+            let point = Point::new(rng.next_u64() as i64, rng.next_u64() as i64);
+            assert_eq!(point, point.to_string().parse().unwrap());
+        }
+    }
+}
@@ -1,176 +1,967 @@
-use std::cmp::{max, min};
-use std::collections::HashMap;
-use std::env;
-
-use adhoc_derive::FromStr;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
 
+use util::cli::{self, Output, SubcommandArgs};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
+use chronal_coordinates::Point;
+
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
+    cli::run_with_subcommands(
+        6,
+        solve,
+        None,
+        &[("areas", areas_command), ("render", render_command)],
+    );
+}
+
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<Point> = FileReader::new().read_from_file(input_file)?;
+
+    let (largest_area, point_with_largest_area) = find_largest_area(&input, Metric::Manhattan);
+
+    output.answer(
+        1,
+        largest_area,
+        format!(
+            "Largest area: {} (Point: ({},{}))",
+            largest_area, point_with_largest_area.x, point_with_largest_area.y
+        ),
+    );
+
+    let size_of_region = find_region(&input, 10000, Metric::Manhattan)?;
+    output.answer(
+        2,
+        size_of_region,
+        format!("Size  of region: {}", size_of_region),
+    );
+
+    Ok(())
+}
+
+/// Prints the area of every coordinate and the largest finite area, and optionally the size of
+/// the region with a total distance below `--threshold`.
+///
+/// Usage: `areas <input file> [--metric manhattan|chebyshev|euclidean] [--threshold <n>]`.
+fn areas_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &[], &["metric", "threshold"])?;
+    let metric = match args.value::<String>("metric")? {
+        Some(metric) => metric
+            .parse()
+            .context(ErrorKind::Usage, "Invalid value for --metric")?,
+        None => Metric::Manhattan,
     };
+    let threshold = args.value::<u64>("threshold")?;
+    let points: Vec<Point> = FileReader::new().read_from_file(args.input_file())?;
 
-    let input: Vec<Point> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
+    for (point, area) in points.iter().zip(find_areas(&points, metric)) {
+        match area {
+            Area::Finite(area) => println!("{}: {}", point, area),
+            Area::Infinite => println!("{}: infinite", point),
         }
+    }
+    let (largest_area, point_with_largest_area) = find_largest_area(&points, metric);
+    println!(
+        "Largest area: {} (Point: ({}))",
+        largest_area, point_with_largest_area
+    );
+    if let Some(threshold) = threshold {
+        println!(
+            "Size of region: {}",
+            find_region(&points, threshold, metric)?
+        );
+    }
+
+    Ok(())
+}
+
+/// Writes the map of areas as a PPM image.
+///
+/// Usage: `render <input file> --output <file> [--metric manhattan|chebyshev|euclidean]
+/// [--threshold <n>] [--scale <n>] [--margin <n>]`. Every grid point within the bounding box of
+/// the coordinates, extended by `--margin` (default 1), becomes a square of `--scale` (default
+/// 4) pixels. See [`render`] for the colours.
+fn render_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(
+        args,
+        &[],
+        &["output", "metric", "threshold", "scale", "margin"],
+    )?;
+    let output = args
+        .value::<String>("output")?
+        .ok_or_else(|| Error::usage("Please supply output file with --output!"))?;
+    let metric = match args.value::<String>("metric")? {
+        Some(metric) => metric
+            .parse()
+            .context(ErrorKind::Usage, "Invalid value for --metric")?,
+        None => Metric::Manhattan,
     };
+    let threshold = args.value::<u64>("threshold")?;
+    let scale = args.value::<usize>("scale")?.unwrap_or(4);
+    let margin = args.value::<i64>("margin")?.unwrap_or(1);
+    if scale == 0 || margin < 0 {
+        return Err(Error::usage(
+            "--scale must be positive and --margin not negative",
+        ));
+    }
+    let points: Vec<Point> = FileReader::new().read_from_file(args.input_file())?;
+
+    let image = render(&points, metric, threshold, margin)?;
+    let scaled_pixels = (image.width as u128 * image.height as u128)
+        .saturating_mul(scale as u128)
+        .saturating_mul(scale as u128);
+    if scaled_pixels > MAX_PIXELS {
+        return Err(Error::usage(format!(
+            "An image of {}x{} grid points is too large to render at --scale {}",
+            image.width, image.height, scale
+        )));
+    }
+    let mut writer = BufWriter::new(File::create(&output).context(
+        ErrorKind::Io,
+        format!("Error creating output file \"{}\"", output),
+    )?);
+    image
+        .write_ppm(&mut writer, scale)
+        .and_then(|_| writer.flush())
+        .context(ErrorKind::Io, format!("Error writing \"{}\"", output))?;
 
-    let (max_width, max_height) = calculate_dimensions(&input);
+    Ok(())
+}
 
-    let (largest_area, point_with_largest_area) = find_largest_area(&input, max_width, max_height);
+/// RGB colour.
+type Color = [u8; 3];
 
-    println!(
-        "Largest area: {} (Point: ({},{}))",
-        largest_area, point_with_largest_area.x, point_with_largest_area.y
-    );
+const TIE_COLOR: Color = [0, 0, 0];
+const COORDINATE_COLOR: Color = [255, 255, 255];
 
-    let size_of_region = find_region(&input, 10000, max_width, max_height);
-    println!("Size  of region: {}", size_of_region);
+/// Largest number of pixels of a rendered image.
+const MAX_PIXELS: u128 = 1 << 26;
+
+/// RGB image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
 }
 
-fn find_largest_area(points: &[Point], max_width: usize, max_height: usize) -> (usize, Point) {
-    let mut map = HashMap::new();
-    for x in 0..=max_width {
-        for y in 0..=max_height {
-            let p = Point::new(x, y);
-            let mut shortest_dist = usize::max_value();
-            let mut closest_point = None;
-            for (dist, q) in points.iter().map(|q| (p.manhattan_distance_to(&q), q)) {
-                if dist < shortest_dist {
-                    shortest_dist = dist;
-                    closest_point = Some(*q);
-                } else if dist == shortest_dist {
-                    closest_point = None;
+impl Image {
+    /// Creates a black image of the given size.
+    fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![[0; 3]; width * height],
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Writes the image as binary PPM, drawing every pixel as a square of `scale` pixels.
+    fn write_ppm<W: Write>(&self, writer: &mut W, scale: usize) -> std::io::Result<()> {
+        let scaled = |size: usize| {
+            size.checked_mul(scale).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Scaled image too large")
+            })
+        };
+        let (width, height) = (scaled(self.width)?, scaled(self.height)?);
+        write!(writer, "P6\n{} {}\n255\n", width, height)?;
+        let mut row = Vec::with_capacity(scaled(width)?.saturating_mul(3));
+        for y in 0..self.height {
+            row.clear();
+            for x in 0..self.width {
+                for _ in 0..scale {
+                    row.extend_from_slice(&self.get(x, y));
                 }
             }
+            for _ in 0..scale {
+                writer.write_all(&row)?;
+            }
+        }
+        Ok(())
+    }
+}
 
-            if let Some(q) = closest_point {
-                let counter = map.entry(q).or_insert(0usize);
+/// Renders the grid points within the bounding box of `points`, extended by `margin` (but not
+/// beyond the limits of `i64`), with one pixel per grid point (x to the right, y downwards).
+/// Returns an error if there are no points, or more than `MAX_PIXELS` grid points.
+///
+/// Every coordinate gets its own colour, which is darkened for infinite areas. Grid points
+/// closest to several coordinates are black, the coordinates themselves white. If a
+/// `distance_threshold` is given, the region with a total distance below it is lightened.
+fn render(
+    points: &[Point],
+    metric: Metric,
+    distance_threshold: Option<u64>,
+    margin: i64,
+) -> Result<Image, Error> {
+    let (min_x, max_x, min_y, max_y) = bounds(points.iter().map(|p| (p.x, p.y)))
+        .ok_or_else(|| Error::runtime("No coordinates to render"))?;
+    let (min_x, max_x) = (min_x.saturating_sub(margin), max_x.saturating_add(margin));
+    let (min_y, max_y) = (min_y.saturating_sub(margin), max_y.saturating_add(margin));
+    let width = (max_x as i128 - min_x as i128 + 1) as u128;
+    let height = (max_y as i128 - min_y as i128 + 1) as u128;
+    if width.saturating_mul(height) > MAX_PIXELS {
+        return Err(Error::usage(format!(
+            "An image of {}x{} grid points is too large to render",
+            width, height
+        )));
+    }
+    let (width, height) = (width as usize, height as usize);
 
-                if p.x == 0 || p.x == max_width || p.y == 0 || p.y == max_height {
-                    *counter = usize::max_value();
-                } else {
-                    *counter = (*counter).saturating_add(1);
+    let colors: Vec<Color> = find_areas(points, metric)
+        .iter()
+        .enumerate()
+        .map(|(i, area)| {
+            let color = area_color(i);
+            match area {
+                Area::Finite(_) => color,
+                Area::Infinite => mix(color, [0; 3]),
+            }
+        })
+        .collect();
+
+    let mut image = Image::new(width, height);
+    for (y, grid_y) in (min_y..=max_y).enumerate() {
+        for (x, grid_x) in (min_x..=max_x).enumerate() {
+            let p = Point::new(grid_x, grid_y);
+            let mut color = match closest(points, &p, metric) {
+                Some(i) if points[i] == p => COORDINATE_COLOR,
+                Some(i) => colors[i],
+                None => TIE_COLOR,
+            };
+            if let Some(threshold) = distance_threshold {
+                let sum_of_distances: f64 = points.iter().map(|q| metric.distance(&p, q)).sum();
+                if sum_of_distances < threshold as f64 {
+                    color = mix(color, [255; 3]);
                 }
             }
+            image.set(x, y, color);
+        }
+    }
+
+    Ok(image)
+}
+
+/// Returns a bright colour for the `i`-th area, with hues spaced by the golden angle so that
+/// neighbouring indices get clearly distinct colours.
+fn area_color(i: usize) -> Color {
+    let hue = (i as f64 * 137.507_764) % 360.0 / 60.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let channel = |c: f64| (63.0 + c * 192.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+/// Returns the colour halfway between `a` and `b`.
+fn mix(a: Color, b: Color) -> Color {
+    let mut mixed = [0; 3];
+    for (m, (a, b)) in mixed.iter_mut().zip(a.iter().zip(b.iter())) {
+        *m = ((*a as u16 + *b as u16) / 2) as u8;
+    }
+    mixed
+}
+
+/// Distance metric on the grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
+impl Metric {
+    /// Returns a value ordering pairs of points like their distance: the distance itself, or its
+    /// square for the Euclidean metric (to compare distances exactly).
+    fn rank(self, p: &Point, q: &Point) -> u128 {
+        match self {
+            Metric::Manhattan => p.manhattan_distance_to(q),
+            Metric::Chebyshev => u128::from(p.chebyshev_distance_to(q)),
+            Metric::Euclidean => p.squared_euclidean_distance_to(q),
+        }
+    }
+
+    /// Returns the distance between `p` and `q`.
+    fn distance(self, p: &Point, q: &Point) -> f64 {
+        match self {
+            Metric::Manhattan | Metric::Chebyshev => self.rank(p, q) as f64,
+            Metric::Euclidean => (p.squared_euclidean_distance_to(q) as f64).sqrt(),
         }
     }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manhattan" => Ok(Metric::Manhattan),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            "euclidean" => Ok(Metric::Euclidean),
+            s => Err(format!(
+                "Unknown metric \"{}\" (expected \"manhattan\", \"chebyshev\" or \"euclidean\")",
+                s
+            )),
+        }
+    }
+}
 
-    map.iter()
-        .filter(|(_, &area)| area < usize::max_value())
-        .max_by_key(|(_, &area)| area)
-        .map(|(&point, &area)| (area, point))
+/// Number of grid points closer to a coordinate than to any other coordinate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Area {
+    Finite(usize),
+    Infinite,
+}
+
+/// Returns the size of the largest finite area, together with its coordinate.
+fn find_largest_area(points: &[Point], metric: Metric) -> (usize, Point) {
+    points
+        .iter()
+        .zip(find_areas(points, metric))
+        .filter_map(|(&point, area)| match area {
+            Area::Finite(area) => Some((area, point)),
+            Area::Infinite => None,
+        })
+        .max_by_key(|&(area, _)| area)
         .unwrap_or_else(|| (0, Point::new(0, 0)))
 }
 
-fn find_region(
-    points: &[Point],
-    distance_threshold: usize,
-    max_width: usize,
-    max_height: usize,
-) -> usize {
-    let mut size_of_region = 0;
-    for x in 0..=max_width {
-        for y in 0..=max_height {
+/// Returns the area of each of `points`.
+fn find_areas(points: &[Point], metric: Metric) -> Vec<Area> {
+    match metric {
+        Metric::Manhattan | Metric::Chebyshev => find_areas_by_projection(points, metric),
+        Metric::Euclidean => find_euclidean_areas(points),
+    }
+}
+
+/// Returns the index of the point closest to `p`, or `None` if several points are closest.
+fn closest(points: &[Point], p: &Point, metric: Metric) -> Option<usize> {
+    let mut shortest_dist = u128::MAX;
+    let mut closest_point = None;
+    for (i, q) in points.iter().enumerate() {
+        let dist = metric.rank(p, q);
+        if dist < shortest_dist {
+            shortest_dist = dist;
+            closest_point = Some(i);
+        } else if dist == shortest_dist {
+            closest_point = None;
+        }
+    }
+    closest_point
+}
+
+/// Computes the areas for the Manhattan or Chebyshev metric.
+///
+/// In frame coordinates `(u, v)`, i.e. `(x, y)` for the Manhattan metric and `(x + y, x - y)`
+/// for the Chebyshev metric (which turns it into half the Manhattan metric), the distance is the
+/// sum of the coordinate differences. Moving a grid point on the bounding box of the
+/// coordinates (in frame coordinates) outward thus increases its distance to all coordinates by
+/// the same amount, and moving a point beyond the bounding box inward decreases them by the same
+/// amount, without changing the closest coordinate. So an area is infinite iff it contains a
+/// grid point on or just beyond the bounding box, and finite areas lie within it.
+fn find_areas_by_projection(points: &[Point], metric: Metric) -> Vec<Area> {
+    // In `i128`, as the Chebyshev frame coordinates can exceed the range of `i64`
+    let frame = |p: &Point| {
+        let (x, y) = (i128::from(p.x), i128::from(p.y));
+        match metric {
+            Metric::Manhattan => (x, y),
+            _ => (x + y, x - y),
+        }
+    };
+    let (min_u, max_u, min_v, max_v) = match bounds(points.iter().map(frame)) {
+        Some(bounds) => bounds,
+        None => return Vec::new(),
+    };
+
+    // Grid points with frame coordinates within the bounding box, extended by one. A step
+    // outward changes the frame coordinates by 2 for the Chebyshev metric, so all grid points
+    // beyond the bounding box can be moved into this margin.
+    let ((min_x, max_x), (min_y, max_y)) = match metric {
+        Metric::Manhattan => ((min_u - 1, max_u + 1), (min_v - 1, max_v + 1)),
+        _ => (
+            (
+                (min_u + min_v - 2).div_euclid(2),
+                (max_u + max_v + 3).div_euclid(2),
+            ),
+            (
+                (min_u - max_v - 2).div_euclid(2),
+                (max_u - min_v + 3).div_euclid(2),
+            ),
+        ),
+    };
+    let clamp = |c: i128| c.clamp(i64::MIN.into(), i64::MAX.into()) as i64;
+
+    let mut areas = vec![Area::Finite(0); points.len()];
+    for x in clamp(min_x)..=clamp(max_x) {
+        for y in clamp(min_y)..=clamp(max_y) {
             let p = Point::new(x, y);
-            let sum_of_distances: usize = points.iter().map(|q| p.manhattan_distance_to(&q)).sum();
+            let (u, v) = frame(&p);
+            if u < min_u - 1 || u > max_u + 1 || v < min_v - 1 || v > max_v + 1 {
+                continue;
+            }
 
-            if sum_of_distances < distance_threshold {
-                size_of_region += 1;
+            if let Some(i) = closest(points, &p, metric) {
+                if u <= min_u || u >= max_u || v <= min_v || v >= max_v {
+                    areas[i] = Area::Infinite;
+                } else if let Area::Finite(area) = &mut areas[i] {
+                    *area += 1;
+                }
             }
         }
     }
 
-    size_of_region
+    areas
 }
 
-fn calculate_dimensions(points: &[Point]) -> (usize, usize) {
+/// Computes the areas for the Euclidean metric.
+///
+/// The area of a coordinate is infinite iff it lies on the boundary of the convex hull of all
+/// coordinates: then there is a direction in which it's strictly closer than all coordinates
+/// not on the same hull edge, and closer than those on the same hull edge along the edge's
+/// normal. Coordinates inside the hull have a bounded Voronoi cell, whose grid points are
+/// counted.
+fn find_euclidean_areas(points: &[Point]) -> Vec<Area> {
+    let on_boundary = on_convex_hull_boundary(points);
     points
         .iter()
-        .fold((0, 0), |m, p| (max(m.0, p.x), max(m.1, p.y)))
+        .enumerate()
+        .map(|(i, p)| {
+            if points.iter().filter(|&q| q == p).count() > 1 {
+                // Duplicate coordinates tie everywhere
+                Area::Finite(0)
+            } else if on_boundary[i] {
+                Area::Infinite
+            } else {
+                Area::Finite(euclidean_cell_area(points, i))
+            }
+        })
+        .collect()
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, FromStr)]
-#[adhoc(regex = r"^(?P<x>\d+), (?P<y>\d+)$")]
-struct Point {
-    x: usize,
-    y: usize,
+/// Returns for each of `points` whether it lies on the boundary of their convex hull (on a
+/// vertex or an edge). If the points are collinear, all of them do.
+fn on_convex_hull_boundary(points: &[Point]) -> Vec<bool> {
+    let cross = |o: &Point, a: &Point, b: &Point| {
+        (a.x - o.x) as i128 * (b.y - o.y) as i128 - (a.y - o.y) as i128 * (b.x - o.x) as i128
+    };
+
+    // Andrew's monotone chain, keeping only the corners
+    let mut sorted: Vec<Point> = points.to_vec();
+    sorted.sort_unstable_by_key(|p| (p.x, p.y));
+    sorted.dedup();
+    let chain = |points: &mut dyn Iterator<Item = &Point>| {
+        let mut chain: Vec<Point> = Vec::new();
+        for p in points {
+            while chain.len() >= 2
+                && cross(&chain[chain.len() - 2], &chain[chain.len() - 1], p) <= 0
+            {
+                chain.pop();
+            }
+            chain.push(*p);
+        }
+        chain.pop();
+        chain
+    };
+    let mut hull = chain(&mut sorted.iter());
+    hull.extend(chain(&mut sorted.iter().rev()));
+    if hull.len() < 3 {
+        return vec![true; points.len()];
+    }
+
+    points
+        .iter()
+        .map(|p| {
+            (0..hull.len()).any(|i| {
+                let (a, b) = (&hull[i], &hull[(i + 1) % hull.len()]);
+                cross(a, b, p) == 0
+                    && a.x.min(b.x) <= p.x
+                    && p.x <= a.x.max(b.x)
+                    && a.y.min(b.y) <= p.y
+                    && p.y <= a.y.max(b.y)
+            })
+        })
+        .collect()
 }
 
-impl Point {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
+/// Counts the grid points closer to `points[i]` than to all other points, for a point with a
+/// bounded Voronoi cell.
+///
+/// The cell is computed by clipping a square with the half-planes closer to `points[i]` than to
+/// each other point, and only grid points within its bounding box are checked. The vertices of a
+/// bounded cell are circumcentres of triangles of grid points, so their distance from
+/// `points[i]` is at most `d^3 / 2`, where `d` is the diameter of the point set.
+fn euclidean_cell_area(points: &[Point], i: usize) -> usize {
+    let p = points[i];
+    let (min_x, max_x, min_y, max_y) = bounds(points.iter().map(|p| (p.x, p.y))).unwrap();
+    let diameter = ((max_x - min_x) as f64).hypot((max_y - min_y) as f64);
+    let r = diameter.powi(3) / 2.0 + 1.0;
+
+    let (px, py) = (p.x as f64, p.y as f64);
+    let mut cell = vec![
+        (px - r, py - r),
+        (px + r, py - r),
+        (px + r, py + r),
+        (px - r, py + r),
+    ];
+    for q in points.iter().filter(|&q| *q != p) {
+        // Closer to p than to q: 2 * x * (q - p) <= |q|^2 - |p|^2
+        let normal = (2.0 * (q.x - p.x) as f64, 2.0 * (q.y - p.y) as f64);
+        let norm_q = q.x as i128 * q.x as i128 + q.y as i128 * q.y as i128;
+        let norm_p = p.x as i128 * p.x as i128 + p.y as i128 * p.y as i128;
+        cell = clip(&cell, normal, (norm_q - norm_p) as f64);
     }
 
-    fn manhattan_distance_to(&self, other: &Point) -> usize {
-        max(self.x, other.x) - min(self.x, other.x) + max(self.y, other.y) - min(self.y, other.y)
+    let x_range = cell
+        .iter()
+        .map(|v| v.0)
+        .fold((f64::MAX, f64::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    let y_range = cell
+        .iter()
+        .map(|v| v.1)
+        .fold((f64::MAX, f64::MIN), |(lo, hi), y| (lo.min(y), hi.max(y)));
+
+    let mut area = 0;
+    for x in x_range.0.floor() as i64 - 1..=x_range.1.ceil() as i64 + 1 {
+        for y in y_range.0.floor() as i64 - 1..=y_range.1.ceil() as i64 + 1 {
+            if closest(points, &Point::new(x, y), Metric::Euclidean) == Some(i) {
+                area += 1;
+            }
+        }
     }
+    area
+}
+
+/// Clips the convex `polygon` to the half-plane `normal . x <= offset`.
+fn clip(polygon: &[(f64, f64)], normal: (f64, f64), offset: f64) -> Vec<(f64, f64)> {
+    let side = |v: (f64, f64)| normal.0 * v.0 + normal.1 * v.1 - offset;
+
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, &v) in polygon.iter().enumerate() {
+        let w = polygon[(i + 1) % polygon.len()];
+        let (side_v, side_w) = (side(v), side(w));
+        if side_v <= 0.0 {
+            clipped.push(v);
+        }
+        if (side_v < 0.0 && side_w > 0.0) || (side_v > 0.0 && side_w < 0.0) {
+            let t = side_v / (side_v - side_w);
+            clipped.push((v.0 + t * (w.0 - v.0), v.1 + t * (w.1 - v.1)));
+        }
+    }
+    clipped
+}
+
+/// Largest number of coordinates (along an axis for the Manhattan metric, grid points otherwise)
+/// that are visited to find a region.
+const MAX_REGION_SCAN: u128 = 1 << 28;
+
+/// Returns the size of the region of grid points whose total distance to all `points` is less
+/// than `distance_threshold`, or an error if it's too large to be enumerated.
+fn find_region(points: &[Point], distance_threshold: u64, metric: Metric) -> Result<usize, Error> {
+    match metric {
+        Metric::Manhattan => find_manhattan_region(points, distance_threshold),
+        _ => find_region_by_scan(points, distance_threshold, metric),
+    }
+}
+
+/// Computes the region for the Manhattan metric.
+///
+/// The total distance of `(x, y)` separates into `s_x(x) + s_y(y)`, the sums of the distances
+/// along each axis. So only the axis sums below the threshold need to be computed, and the
+/// region consists of all pairs of them with a sum below the threshold, which are counted by
+/// binary search instead of visiting every grid point.
+fn find_manhattan_region(points: &[Point], distance_threshold: u64) -> Result<usize, Error> {
+    let sums_x = axis_distance_sums(points.iter().map(|p| p.x).collect(), distance_threshold)?;
+    let sums_y = axis_distance_sums(points.iter().map(|p| p.y).collect(), distance_threshold)?;
+
+    Ok(sums_x
+        .iter()
+        .map(|&sum_x| sums_y.partition_point(|&sum_y| sum_y < distance_threshold - sum_x))
+        .sum())
+}
+
+/// Returns the sums of distances `s(c) = sum(|c - c_i|)` to `coordinates` for all integers `c`
+/// with `s(c) < distance_threshold`, in ascending order, or an error if there are more than
+/// `MAX_REGION_SCAN` of them.
+///
+/// Each sum is computed in `O(log n)` from prefix sums of the sorted coordinates. As `s` is
+/// convex with its minimum at the median, the `c` with `s(c) < distance_threshold` form an
+/// interval, whose ends are found by binary search.
+fn axis_distance_sums(
+    mut coordinates: Vec<i64>,
+    distance_threshold: u64,
+) -> Result<Vec<u64>, Error> {
+    if coordinates.is_empty() {
+        return Ok(Vec::new());
+    }
+    coordinates.sort_unstable();
+    let n = coordinates.len();
+    let mut prefix_sums = Vec::with_capacity(n + 1);
+    prefix_sums.push(0i128);
+    for &c in coordinates.iter() {
+        prefix_sums.push(prefix_sums[prefix_sums.len() - 1] + c as i128);
+    }
+    let sum = |c: i64| {
+        let below = coordinates.partition_point(|&c_i| c_i <= c);
+        (c as i128 * below as i128 - prefix_sums[below])
+            + (prefix_sums[n] - prefix_sums[below] - c as i128 * (n - below) as i128)
+    };
+    let in_region = |c: i64| sum(c) < distance_threshold as i128;
+
+    let median = coordinates[(n - 1) / 2];
+    if !in_region(median) {
+        return Ok(Vec::new());
+    }
+    let first = first_where(i64::MIN, median, in_region);
+    let last = if in_region(i64::MAX) {
+        i64::MAX
+    } else {
+        first_where(median, i64::MAX, |c| !in_region(c)) - 1
+    };
+    if (last as i128 - first as i128) as u128 >= MAX_REGION_SCAN {
+        return Err(Error::usage(format!(
+            "The region with a total distance below {} is too large",
+            distance_threshold
+        )));
+    }
+
+    let mut sums: Vec<u64> = (first..=last).map(|c| sum(c) as u64).collect();
+    sums.sort_unstable();
+    Ok(sums)
+}
+
+/// Returns the smallest `c` in `low..=high` for which `predicate` holds, given that it holds for
+/// `high` and for every `c` following one for which it holds.
+fn first_where<P: Fn(i64) -> bool>(mut low: i64, mut high: i64, predicate: P) -> i64 {
+    while low < high {
+        let mid = (low as i128 + high as i128).div_euclid(2) as i64;
+        if predicate(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low
+}
+
+/// Computes the region by summing up the distances of every grid point that may be part of it.
+///
+/// All distances of a grid point `k` steps beyond the bounding box of the points (in x or y
+/// direction) are at least `k` in every metric, so the region extends at most
+/// `distance_threshold / points.len()` beyond it. Returns an error if that's more than
+/// `MAX_REGION_SCAN` grid points.
+fn find_region_by_scan(
+    points: &[Point],
+    distance_threshold: u64,
+    metric: Metric,
+) -> Result<usize, Error> {
+    let (min_x, max_x, min_y, max_y) = match bounds(points.iter().map(|p| (p.x, p.y))) {
+        Some(bounds) => bounds,
+        None => return Ok(0),
+    };
+    let margin = i64::try_from(distance_threshold / points.len() as u64).unwrap_or(i64::MAX);
+    let span = |min: i64, max: i64| {
+        (max.saturating_add(margin) as i128 - min.saturating_sub(margin) as i128 + 1) as u128
+    };
+    if span(min_x, max_x).saturating_mul(span(min_y, max_y)) > MAX_REGION_SCAN {
+        return Err(Error::usage(format!(
+            "The region with a total distance below {} is too large",
+            distance_threshold
+        )));
+    }
+
+    let mut size_of_region = 0;
+    for x in min_x.saturating_sub(margin)..=max_x.saturating_add(margin) {
+        for y in min_y.saturating_sub(margin)..=max_y.saturating_add(margin) {
+            let p = Point::new(x, y);
+            let sum_of_distances: f64 = points.iter().map(|q| metric.distance(&p, q)).sum();
+
+            if sum_of_distances < distance_threshold as f64 {
+                size_of_region += 1;
+            }
+        }
+    }
+
+    Ok(size_of_region)
+}
+
+/// Returns the bounding box `(min_x, max_x, min_y, max_y)` of `points`, or `None` if there are
+/// no points.
+fn bounds<T: Ord + Copy, I: Iterator<Item = (T, T)>>(points: I) -> Option<(T, T, T, T)> {
+    points.fold(None, |bounds, (x, y)| match bounds {
+        None => Some((x, x, y, y)),
+        Some((min_x, max_x, min_y, max_y)) => {
+            Some((min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y)))
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use util::random::Rng;
 
-    #[test]
-    fn test_manhattan_distance() {
-        let p = Point::new(0, 0);
-        let q = Point::new(5, 2);
-        assert_eq!(7, p.manhattan_distance_to(&q));
-    }
-
-    #[test]
-    fn test_largest_area() {
-        let points = vec![
+    fn example() -> Vec<Point> {
+        vec![
             Point::new(1, 1),
             Point::new(1, 6),
             Point::new(8, 3),
             Point::new(3, 4),
             Point::new(5, 5),
             Point::new(8, 9),
-        ];
+        ]
+    }
 
-        let (max_width, max_height) = calculate_dimensions(&points);
+    /// Counts the grid points closest to each point within the bounding box of the points,
+    /// extended by `margin`.
+    fn count_in_box(points: &[Point], metric: Metric, margin: i64) -> Vec<usize> {
+        let (min_x, max_x, min_y, max_y) = bounds(points.iter().map(|p| (p.x, p.y))).unwrap();
+        let mut counts = vec![0; points.len()];
+        for x in min_x - margin..=max_x + margin {
+            for y in min_y - margin..=max_y + margin {
+                if let Some(i) = closest(points, &Point::new(x, y), metric) {
+                    counts[i] += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_largest_area() {
         let (largest_area, point_with_largest_area) =
-            find_largest_area(&points, max_width, max_height);
+            find_largest_area(&example(), Metric::Manhattan);
         assert_eq!(17, largest_area);
         assert_eq!(Point::new(5, 5), point_with_largest_area);
+
+        use Area::*;
+        assert_eq!(
+            vec![
+                Infinite,
+                Infinite,
+                Infinite,
+                Finite(9),
+                Finite(17),
+                Infinite
+            ],
+            find_areas(&example(), Metric::Manhattan)
+        );
+    }
+
+    #[test]
+    fn test_negative_coordinates() {
+        let shifted: Vec<Point> = example()
+            .iter()
+            .map(|p| Point::new(p.x - 10, p.y - 20))
+            .collect();
+        for &metric in &[Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean] {
+            assert_eq!(find_areas(&example(), metric), find_areas(&shifted, metric));
+            assert_eq!(
+                find_region(&example(), 32, metric).unwrap(),
+                find_region(&shifted, 32, metric).unwrap()
+            );
+        }
+
+        // Frame coordinates beyond the range of `i64`
+        for &(dx, dy) in &[(i64::MIN, i64::MIN), (i64::MAX - 9, i64::MAX - 9)] {
+            let shifted: Vec<Point> = example()
+                .iter()
+                .map(|p| Point::new(p.x + dx, p.y + dy))
+                .collect();
+            for &metric in &[Metric::Manhattan, Metric::Chebyshev] {
+                assert_eq!(find_areas(&example(), metric), find_areas(&shifted, metric));
+            }
+        }
+    }
+
+    #[test]
+    fn test_areas_match_brute_force() {
+        let mut rng = Rng::new(42);
+        for round in 0..20 {
+            let points: Vec<Point> = (0..rng.range_usize(1, 12))
+                .map(|_| Point::new(rng.range(-10, 10), rng.range(-10, 10)))
+                .collect();
+            for &metric in &[Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean] {
+                // Finite Euclidean areas may reach far beyond the points along thin slivers
+                let margin = if metric == Metric::Euclidean { 150 } else { 30 };
+                let areas = find_areas(&points, metric);
+                let small = count_in_box(&points, metric, margin);
+                let large = count_in_box(&points, metric, 2 * margin);
+                for i in 0..points.len() {
+                    let expected = if small[i] == large[i] {
+                        Area::Finite(small[i])
+                    } else {
+                        Area::Infinite
+                    };
+                    assert_eq!(
+                        expected, areas[i],
+                        "round {}, {:?}, point {}: {:?}",
+                        round, metric, points[i], points
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        assert_eq!(
+            Ok(Metric::Chebyshev),
+            "chebyshev".parse::<Metric>().map_err(|_| ())
+        );
+        assert!("taxicab".parse::<Metric>().is_err());
+
+        let (p, q) = (Point::new(1, 1), Point::new(4, 5));
+        assert_eq!(7.0, Metric::Manhattan.distance(&p, &q));
+        assert_eq!(4.0, Metric::Chebyshev.distance(&p, &q));
+        assert_eq!(5.0, Metric::Euclidean.distance(&p, &q));
+        assert_eq!(25, Metric::Euclidean.rank(&p, &q));
     }
 
     #[test]
     fn test_find_region() {
-        let points = vec![
-            Point::new(1, 1),
-            Point::new(1, 6),
-            Point::new(8, 3),
-            Point::new(3, 4),
-            Point::new(5, 5),
-            Point::new(8, 9),
-        ];
-        let (max_width, max_height) = calculate_dimensions(&points);
-        assert_eq!(16, find_region(&points, 32, max_width, max_height));
+        assert_eq!(16, find_region(&example(), 32, Metric::Manhattan).unwrap());
+
+        // The region may extend beyond the bounding box of the points
+        let points = vec![Point::new(0, 0), Point::new(1, 0)];
+        assert_eq!(
+            (-5..=5)
+                .flat_map(|x| (-5..=5).map(move |y| Point::new(x, y)))
+                .filter(|p| points
+                    .iter()
+                    .map(|q| p.manhattan_distance_to(q))
+                    .sum::<u128>()
+                    < 6)
+                .count(),
+            find_region(&points, 6, Metric::Manhattan).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_manhattan_region_matches_scan() {
+        let mut rng = Rng::new(43);
+        for _ in 0..50 {
+            let points: Vec<Point> = (0..rng.range_usize(1, 15))
+                .map(|_| Point::new(rng.range(-20, 20), rng.range(-20, 20)))
+                .collect();
+            let threshold = rng.range(0, 600) as u64;
+            assert_eq!(
+                find_region_by_scan(&points, threshold, Metric::Manhattan).unwrap(),
+                find_manhattan_region(&points, threshold).unwrap(),
+                "{:?} < {}",
+                points,
+                threshold
+            );
+        }
+        assert_eq!(0, find_manhattan_region(&[], 100).unwrap());
+    }
+
+    #[test]
+    fn test_axis_distance_sums() {
+        assert_eq!(
+            vec![2, 2, 2, 4, 4],
+            axis_distance_sums(vec![0, 2], 6).unwrap()
+        );
+        assert!(axis_distance_sums(vec![0, 2], 2).unwrap().is_empty());
+
+        // The region is cut off at the limits of `i64`
+        assert_eq!(
+            vec![0, 1, 1, 2],
+            axis_distance_sums(vec![i64::MAX - 1], 3).unwrap()
+        );
+        assert_eq!(
+            vec![0, 1, 1, 2],
+            axis_distance_sums(vec![i64::MIN + 1], 3).unwrap()
+        );
+
+        // Only the interval below the threshold is visited, however far apart the coordinates
+        let far = vec![-(1 << 62), 0, 1 << 62];
+        assert_eq!(
+            vec![1 << 63, (1 << 63) + 1, (1 << 63) + 1],
+            axis_distance_sums(far.clone(), (1 << 63) + 2).unwrap()
+        );
+        assert!(axis_distance_sums(far, 1 << 63).unwrap().is_empty());
+
+        let error = axis_distance_sums(vec![0], u64::MAX).unwrap_err();
+        assert_eq!(ErrorKind::Usage, error.kind());
+        let points = [Point::new(0, 0)];
+        assert!(find_region(&points, u64::MAX, Metric::Manhattan).is_err());
+        assert!(find_region(&points, u64::MAX, Metric::Chebyshev).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let points = example();
+        let areas = find_areas(&points, Metric::Manhattan);
+        let image = render(&points, Metric::Manhattan, Some(32), 1).unwrap();
+        assert_eq!((10, 11), (image.width, image.height));
+
+        // Coordinates, ties and areas are distinguished
+        assert_eq!(COORDINATE_COLOR, image.get(1, 1));
+        assert_eq!(TIE_COLOR, image.get(5, 0));
+        assert_eq!(Area::Infinite, areas[0]);
+        assert_eq!(mix(area_color(0), [0; 3]), image.get(0, 2));
+        // (5,4) belongs to the finite area of (5,5), and lies in the region
+        assert_eq!(Area::Finite(17), areas[4]);
+        assert_eq!(mix(area_color(4), [255; 3]), image.get(5, 4));
+        assert_ne!(area_color(3), area_color(4));
+
+        assert!(render(&[], Metric::Manhattan, None, 1).is_err());
+
+        // The margin is cut off at the limits of `i64`, and huge images are rejected
+        let corner = [Point::new(i64::MAX, i64::MIN)];
+        let image = render(&corner, Metric::Manhattan, None, 2).unwrap();
+        assert_eq!((3, 3), (image.width, image.height));
+        assert_eq!(COORDINATE_COLOR, image.get(2, 0));
+        let far = [Point::new(i64::MIN, 0), Point::new(i64::MAX, 0)];
+        let error = render(&far, Metric::Manhattan, None, 0).unwrap_err();
+        assert_eq!(ErrorKind::Usage, error.kind());
+        assert!(render(&example(), Metric::Manhattan, None, i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut image = Image::new(2, 1);
+        image.set(1, 0, [1, 2, 3]);
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm, 2).unwrap();
+
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _ in 0..2 {
+            expected.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 2, 3, 1, 2, 3]);
+        }
+        assert_eq!(expected, ppm);
+
+        let error = image.write_ppm(&mut Vec::new(), usize::MAX).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, error.kind());
     }
 
     #[test]
     fn test_part1() {
         let input: Vec<Point> = FileReader::new().read_from_file("input.txt").unwrap();
-        let (max_width, max_height) = calculate_dimensions(&input);
-        let (largest_area, _) = find_largest_area(&input, max_width, max_height);
+        let (largest_area, _) = find_largest_area(&input, Metric::Manhattan);
         assert_eq!(3260, largest_area);
     }
 
     #[test]
     fn test_part2() {
         let input: Vec<Point> = FileReader::new().read_from_file("input.txt").unwrap();
-        let (max_width, max_height) = calculate_dimensions(&input);
-        let size_of_region = find_region(&input, 10000, max_width, max_height);
+        let size_of_region = find_region(&input, 10000, Metric::Manhattan).unwrap();
         assert_eq!(42535, size_of_region);
     }
 }
@@ -0,0 +1,120 @@
+//! Nanobot parser for day 23, shared by the solver and the fuzz targets.
+
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Point in three-dimensional space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Point3D {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Point3D {
+    pub fn manhattan_distance_to(self, other: Point3D) -> usize {
+        ((other.x - self.x).abs() + (other.y - self.y).abs() + (other.z - self.z).abs()) as usize
+    }
+}
+
+/// Nanobot with its position and signal radius.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Nanobot {
+    pub position: Point3D,
+    pub signal_radius: usize,
+}
+
+impl std::fmt::Display for Nanobot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pos=<{},{},{}>, r={}",
+            self.position.x, self.position.y, self.position.z, self.signal_radius
+        )
+    }
+}
+
+impl FromStr for Nanobot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref REGEX: Regex =
+                Regex::new(r"^pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)$").unwrap();
+        }
+        let captures = match REGEX.captures(s) {
+            Some(captures) => captures,
+            None => {
+                return Err("input does not match expected format".to_string());
+            }
+        };
+        let mut values = [0isize; 3];
+        for (i, val) in values.iter_mut().enumerate() {
+            *val = match captures.get(i + 1) {
+                Some(capture) => capture
+                    .as_str()
+                    .parse::<isize>()
+                    .map_err(|e| format!("cannot parse number: {}", e))?,
+                None => {
+                    return Err("input does not match expected format".to_string());
+                }
+            };
+        }
+        let signal_radius = captures[4]
+            .parse::<usize>()
+            .map_err(|e| format!("cannot parse number: {}", e))?;
+        Ok(Nanobot {
+            position: Point3D {
+                x: values[0],
+                y: values[1],
+                z: values[2],
+            },
+            signal_radius,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Nanobot {
+                position: Point3D { x: 1, y: -3, z: 0 },
+                signal_radius: 4,
+            },
+            "pos=<1,-3,0>, r=4".parse().unwrap()
+        );
+
+        for s in &[
+            "",
+            "pos=<1,-3>, r=4",
+            "pos=<1,-3,0>, r=-4",
+            "pos=<1,-3,0>, r=4 ",
+            "pos=<1,-3,99999999999999999999>, r=4",
+        ] {
+            assert!(s.parse::<Nanobot>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(23);
+        for _ in 0..1000 {
+            let nanobot = Nanobot {
+                position: Point3D {
+                    x: rng.range(i64::MIN, i64::MAX) as isize,
+                    y: rng.range(i64::MIN, i64::MAX) as isize,
+                    z: rng.range(i64::MIN, i64::MAX) as isize,
+                },
+                signal_radius: rng.next_u64() as usize,
+            };
+            assert_eq!(nanobot, nanobot.to_string().parse().unwrap());
+        }
+    }
+}
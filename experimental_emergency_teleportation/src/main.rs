@@ -1,120 +1,171 @@
-use std::env;
-use std::str::FromStr;
-
-//use rand::Rng;
-use regex::Regex;
-
-use lazy_static::lazy_static;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
+use util::random::Rng;
+
+use experimental_emergency_teleportation::{Nanobot, Point3D};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run_with_generator(23, solve, generate);
+}
 
-    let input: Vec<Nanobot> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<Nanobot> = FileReader::new().read_from_file(input_file)?;
+    if input.is_empty() {
+        return Err(Error::parse("No nanobots in input"));
+    }
 
     let strongest_nanobot = find_strongest_nanobot(&input);
-    println!("Strongest nanobot: {:?}", strongest_nanobot);
+    output.diagnostic(format!("Strongest nanobot: {:?}", strongest_nanobot));
 
     let number_in_range = number_of_nanobots_in_range(strongest_nanobot, &input);
     let mean_dist = mean_distance(strongest_nanobot.position, &input);
-    println!(
-        "Number of nanobots in range: {} (mean distance: {})",
-        number_in_range, mean_dist
+    output.answer(
+        1,
+        number_in_range,
+        format!(
+            "Number of nanobots in range: {} (mean distance: {})",
+            number_in_range, mean_dist
+        ),
     );
 
-    /* Brute force procedure:
-     * 1) Run random search on whole volume (e.g. x, y, z in -100000000..100000000)
-     * 2) Reduce search volume based on most promising candidates
-     * 3) Repeat step 2, until no new candidates are found in a while
-     * 4) Starting with best candidate, evaluate neighbors in very small bounding box:
-     *    Maximize points in range and then minimize distance to origin
-     * 5) Once stable, cross fingers and hope result is the accepted answer..
-     */
-
-    /* try to maximize points in range and minimize distance to origin */
-    let mut maximum_points_in_range = usize::min_value();
-    let mut minimum_distance = usize::max_value();
-    let origin = Point3D { x: 0, y: 0, z: 0 };
-    // best point found with random search (see below): Point3D { x: 21137940, y: 40831087, z: 57450666 }, points in range: 928, distance: 119419693
-    let mut current = Point3D {
-        x: 21_137_940,
-        y: 40_831_087,
-        z: 57_450_666,
-    };
-    let mut minimum_point = current;
-
-    loop {
-        for x in current.x - 3..current.x + 3 {
-            for y in current.y - 3..current.y + 3 {
-                for z in current.z - 3..current.z + 3 {
-                    let point = Point3D { x, y, z };
-                    let points_in_range = number_of_nanobots_in_range_from_point(point, &input);
-                    if points_in_range >= maximum_points_in_range {
-                        maximum_points_in_range = points_in_range;
-                        let distance = origin.manhattan_distance_to(point);
-                        if distance < minimum_distance {
-                            minimum_distance = distance;
-                            minimum_point = point;
-                        }
-                    }
-                }
+    let (in_range, distance) = find_best_point(&input).expect("input isn't empty");
+    output.answer(
+        2,
+        distance,
+        format!(
+            "Distance of the closest point in range of {} nanobots: {}",
+            in_range, distance
+        ),
+    );
+
+    Ok(())
+}
+
+/// Cube of points `min[i] <= p[i] < min[i] + size`, searched by `find_best_point`.
+///
+/// Cubes are ordered by size first, so that among equally good candidates the search
+/// descends into the smallest one instead of widening across a plateau.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Cube {
+    size: i128,
+    min: [i128; 3],
+}
+
+impl Cube {
+    /// Returns the Manhattan distance from `point` to the closest point of the cube.
+    fn distance_to(&self, point: [i128; 3]) -> i128 {
+        (0..3)
+            .map(|i| {
+                let max = self.min[i] + self.size - 1;
+                (self.min[i] - point[i]).max(point[i] - max).max(0)
+            })
+            .sum()
+    }
+
+    /// Returns the range of `direction · p` over the points `p` of the cube.
+    fn projection(&self, direction: [i128; 3]) -> (i128, i128) {
+        (0..3).fold((0, 0), |(low, high), i| {
+            let max = self.min[i] + self.size - 1;
+            let (a, b) = (direction[i] * self.min[i], direction[i] * max);
+            (low + a.min(b), high + a.max(b))
+        })
+    }
+
+    /// Returns the number of nanobots in range of at least one point of the cube, and a lower
+    /// bound on the distance to the origin of the points of the cube in range of all of them
+    /// (`i128::MAX` if there is no such point).
+    ///
+    /// A range and the points at a given distance from the origin are both bounded by the faces
+    /// of octahedra, so the bound intersects the projections of the cube and of the ranges on
+    /// the directions of those faces. It is exact for a single point.
+    fn bound(&self, bots: &[Nanobot]) -> (usize, i128) {
+        let mut ranges = FACE_DIRECTIONS.map(|direction| self.projection(direction));
+        let mut in_range = 0;
+        for bot in bots {
+            let position = coordinates(bot.position);
+            let radius = bot.signal_radius as i128;
+            if self.distance_to(position) > radius {
+                continue;
+            }
+            in_range += 1;
+            for (range, direction) in ranges.iter_mut().zip(FACE_DIRECTIONS.iter()) {
+                let center: i128 = (0..3).map(|i| direction[i] * position[i]).sum();
+                range.0 = range.0.max(center - radius);
+                range.1 = range.1.min(center + radius);
             }
         }
-        println!(
-            "Minimum point: {:?}, points in range: {}, distance: {}",
-            minimum_point, maximum_points_in_range, minimum_distance
-        );
-        current = minimum_point;
+        let distance = ranges
+            .iter()
+            .map(|&(low, high)| match (low, high) {
+                (low, high) if low > high => i128::MAX,
+                (low, _) if low > 0 => low,
+                (_, high) if high < 0 => -high,
+                _ => 0,
+            })
+            .max()
+            .unwrap();
+        (in_range, distance)
     }
+}
 
-    /* Random search: randomly search points within bounding box */
-    // let mut rng = rand::thread_rng();
-    // let mut maximum_points_in_range = usize::min_value();
-    // let mut minimum_distance = usize::max_value();
-    // let origin = Point3D { x: 0, y: 0, z: 0 };
-    // loop {
-    //     /*
-    //         x = 21000000..23000000
-    //         y = 41000000..43000000
-    //         z = 55000000..57000000
-    //     */
-    //     let rand_x = rng.gen_range(21300000, 22300000);
-    //     let rand_y = rng.gen_range(41000000, 42000000);
-    //     let rand_z = rng.gen_range(57000000, 58000000);
-    //     let point = Point3D {
-    //         x: rand_x,
-    //         y: rand_y,
-    //         z: rand_z,
-    //     };
-    //     let points_in_range = number_of_nanobots_in_range_from_point(point, &input);
-    //     if points_in_range >= maximum_points_in_range {
-    //         if points_in_range > maximum_points_in_range {
-    //             minimum_distance = usize::max_value();
-    //         }
-    //         maximum_points_in_range = points_in_range;
-    //         let distance = origin.manhattan_distance_to(point);
-    //         if distance < minimum_distance {
-    //             minimum_distance = distance;
-    //             println!(
-    //                 "Point: {:?} => points in range: {}, distance: {}",
-    //                 point, points_in_range, distance
-    //             );
-    //         }
-    //     }
-    // }
+/// Normals of the faces of an octahedron, up to sign: the Manhattan distance of `p` to the
+/// origin is the largest `|direction · p|`.
+const FACE_DIRECTIONS: [[i128; 3]; 4] = [[1, 1, 1], [1, 1, -1], [1, -1, 1], [-1, 1, 1]];
+
+fn coordinates(point: Point3D) -> [i128; 3] {
+    [point.x as i128, point.y as i128, point.z as i128]
+}
+
+/// Finds the point in range of the most nanobots, choosing the one closest to the origin among
+/// several, and returns its number of nanobots in range and its distance to the origin.
+///
+/// Starting with a cube containing all nanobots, the cube in range of the most nanobots (an
+/// upper bound for each of its points) is split into eighths until it is a single point. Ties
+/// go to the cube with the lowest bound on the distance to the origin, so the first single
+/// point is the answer.
+fn find_best_point(bots: &[Nanobot]) -> Option<(usize, usize)> {
+    bots.first()?;
+    let mut min = [i128::MAX; 3];
+    let mut max = [i128::MIN; 3];
+    for bot in bots {
+        let position = coordinates(bot.position);
+        let radius = bot.signal_radius as i128;
+        for i in 0..3 {
+            min[i] = min[i].min(position[i] - radius);
+            max[i] = max[i].max(position[i] + radius);
+        }
+    }
+    let mut size = 1;
+    while (0..3).any(|i| min[i] + size <= max[i]) {
+        size *= 2;
+    }
+
+    let mut queue = BinaryHeap::new();
+    let cube = Cube { min, size };
+    let (in_range, distance) = cube.bound(bots);
+    queue.push((in_range, Reverse(distance), Reverse(cube)));
+    while let Some((in_range, Reverse(distance), Reverse(cube))) = queue.pop() {
+        if cube.size == 1 {
+            return Some((in_range, distance as usize));
+        }
+        let size = cube.size / 2;
+        for corner in 0..8 {
+            let mut min = cube.min;
+            for (i, min) in min.iter_mut().enumerate() {
+                if corner & (1 << i) != 0 {
+                    *min += size;
+                }
+            }
+            let eighth = Cube { min, size };
+            let (in_range, distance) = eighth.bound(bots);
+            queue.push((in_range, Reverse(distance), Reverse(eighth)));
+        }
+    }
+    None
 }
 
 fn find_strongest_nanobot(nanobots: &[Nanobot]) -> Nanobot {
@@ -151,72 +202,53 @@ fn number_of_nanobots_in_range(nanobot: Nanobot, others: &[Nanobot]) -> usize {
     number_in_range
 }
 
-fn number_of_nanobots_in_range_from_point(point: Point3D, bots: &[Nanobot]) -> usize {
-    let mut number_in_range = 0;
-    for bot in bots {
-        if point.manhattan_distance_to(bot.position) <= bot.signal_radius {
-            number_in_range += 1;
-        }
+/// Generates a swarm of `size` nanobots.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    const EXTENT: i64 = 100_000_000;
+
+    let mut input = String::new();
+    for _ in 0..size {
+        input.push_str(&format!(
+            "pos=<{},{},{}>, r={}\n",
+            rng.range(-EXTENT, EXTENT),
+            rng.range(-EXTENT, EXTENT),
+            rng.range(-EXTENT, EXTENT),
+            rng.range(EXTENT / 10, EXTENT)
+        ));
     }
-
-    number_in_range
+    input
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct Point3D {
-    x: isize,
-    y: isize,
-    z: isize,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_best_point() {
+        let bots: Vec<Nanobot> = [
+            "pos=<10,12,12>, r=2",
+            "pos=<12,14,12>, r=2",
+            "pos=<16,12,12>, r=4",
+            "pos=<14,14,14>, r=6",
+            "pos=<50,50,50>, r=200",
+            "pos=<10,10,10>, r=5",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+        assert_eq!(Some((5, 36)), find_best_point(&bots));
+        assert_eq!(None, find_best_point(&[]));
 
-impl Point3D {
-    fn manhattan_distance_to(self, other: Point3D) -> usize {
-        ((other.x - self.x).abs() + (other.y - self.y).abs() + (other.z - self.z).abs()) as usize
+        let far: Nanobot = "pos=<-8,-5,-1>, r=4".parse().unwrap();
+        assert_eq!(Some((1, 10)), find_best_point(&[far]));
     }
-}
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct Nanobot {
-    position: Point3D,
-    signal_radius: usize,
-}
-
-impl FromStr for Nanobot {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-            static ref REGEX: Regex =
-                Regex::new(r"^pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)$").unwrap();
-        }
-        let captures = match REGEX.captures(s) {
-            Some(captures) => captures,
-            None => {
-                return Err("input does not match expected format".to_string());
+    #[test]
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(23, solve, generate, size, seed).unwrap();
             }
-        };
-        let mut values = [0isize; 4];
-        for (i, val) in values.iter_mut().enumerate() {
-            *val = match captures.get(i + 1) {
-                Some(capture) => capture
-                    .as_str()
-                    .parse::<isize>()
-                    .map_err(|e| format!("cannot parse number: {}", e))?,
-                None => {
-                    return Err("input does not match expected format".to_string());
-                }
-            };
-        }
-        if captures.get(5).is_some() {
-            return Err("input does not match expected format".to_string());
         }
-        Ok(Nanobot {
-            position: Point3D {
-                x: values[0],
-                y: values[1],
-                z: values[2],
-            },
-            signal_radius: values[3] as usize,
-        })
     }
 }
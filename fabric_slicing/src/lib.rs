@@ -0,0 +1,185 @@
+//! Claim parser for day 3, shared by the solver and the fuzz targets, and queries on the
+//! resulting fabric layout.
+
+use util::rectangle::{GridIndex, Rectangle};
+
+use adhoc_derive::FromStr;
+
+/// Claim of an elf on a rectangular area of the fabric.
+#[derive(Debug, FromStr)]
+#[adhoc(regex = r"^#(?P<owner>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<width>\d+)x(?P<height>\d+)$")]
+pub struct Claim {
+    pub owner: usize,
+    #[adhoc(construct_with = "Claim::rectangle(x, y, width, height)?")]
+    pub rectangle: Rectangle,
+}
+
+impl Claim {
+    /// Returns the rectangle of a claim, or an error if its edges don't fit in an `i64`.
+    fn rectangle(x: i64, y: i64, width: u64, height: u64) -> Result<Rectangle, String> {
+        Rectangle::checked_new(x, y, width, height)
+            .ok_or_else(|| String::from("claim exceeds the fabric"))
+    }
+}
+
+impl std::fmt::Display for Claim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} @ {},{}: {}x{}",
+            self.owner,
+            self.rectangle.x(),
+            self.rectangle.y(),
+            self.rectangle.width(),
+            self.rectangle.height()
+        )
+    }
+}
+
+/// Claims on a piece of fabric, indexed for collision queries.
+#[derive(Debug)]
+pub struct Fabric {
+    claims: Vec<Claim>,
+    index: GridIndex,
+}
+
+impl Fabric {
+    /// Create new `Fabric` from `claims`.
+    pub fn new(claims: Vec<Claim>) -> Self {
+        let index = GridIndex::from_rectangles(claims.iter().map(|claim| claim.rectangle));
+        Self { claims, index }
+    }
+
+    /// Returns the claims, in input order.
+    pub fn claims(&self) -> &[Claim] {
+        &self.claims
+    }
+
+    /// Returns the owners of the claims overlapping the claim(s) of `owner`, sorted by input
+    /// order. Claims of the same owner don't overlap each other.
+    pub fn overlapping(&self, owner: usize) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .claims
+            .iter()
+            .filter(|claim| claim.owner == owner)
+            .flat_map(|claim| self.index.overlapping(&claim.rectangle))
+            .filter(|&id| self.claims[id].owner != owner)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter().map(|id| self.claims[id].owner).collect()
+    }
+
+    /// Returns the owners of the claims covering the square inch at `(x, y)`, sorted by input
+    /// order.
+    pub fn covering(&self, x: i64, y: i64) -> Vec<usize> {
+        self.index
+            .covering(x, y)
+            .into_iter()
+            .map(|id| self.claims[id].owner)
+            .collect()
+    }
+
+    /// Returns the owners of all pairs of overlapping claims, sorted by input order.
+    pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        self.index
+            .overlapping_pairs()
+            .into_iter()
+            .map(|(i, j)| (self.claims[i].owner, self.claims[j].owner))
+            .filter(|(a, b)| a != b)
+            .collect()
+    }
+
+    /// Returns the owner of the first claim that doesn't overlap any other claim.
+    pub fn non_overlapping_claim(&self) -> Option<usize> {
+        let mut overlapping = vec![false; self.claims.len()];
+        for (i, j) in self.index.overlapping_pairs() {
+            if self.claims[i].owner != self.claims[j].owner {
+                overlapping[i] = true;
+                overlapping[j] = true;
+            }
+        }
+
+        self.claims
+            .iter()
+            .zip(overlapping)
+            .find(|(_, overlapping)| !overlapping)
+            .map(|(claim, _)| claim.owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    #[test]
+    fn test_parse() {
+        let claim: Claim = "#123 @ 3,2: 5x4".parse().unwrap();
+        assert_eq!(123, claim.owner);
+        assert_eq!(
+            (3, 2, 5, 4),
+            (
+                claim.rectangle.x(),
+                claim.rectangle.y(),
+                claim.rectangle.width(),
+                claim.rectangle.height()
+            )
+        );
+
+        for s in &[
+            "",
+            "#123 @ 3,2: 5x",
+            "#123 @ 3,-2: 5x4",
+            "#123 @ 3,2: 5x4 ",
+            "#123 @ 3,2: 5x99999999999999999999",
+            "#123 @ 9223372036854775807,2: 1x4",
+            "#123 @ 3,2: 5x9223372036854775806",
+        ] {
+            assert!(s.parse::<Claim>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_fabric() {
+        let claims: Vec<Claim> = [
+            "#1 @ 1,3: 4x4",
+            "#2 @ 3,1: 4x4",
+            "#3 @ 5,5: 2x2",
+            "#4 @ 0,0: 4x2",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+        let fabric = Fabric::new(claims);
+
+        assert_eq!(4, fabric.claims().len());
+        assert_eq!(vec![2], fabric.overlapping(1));
+        assert_eq!(vec![1, 4], fabric.overlapping(2));
+        assert!(fabric.overlapping(3).is_empty());
+        assert!(fabric.overlapping(5).is_empty());
+        assert_eq!(vec![1, 2], fabric.covering(3, 3));
+        assert_eq!(vec![3], fabric.covering(6, 6));
+        assert!(fabric.covering(7, 7).is_empty());
+        assert_eq!(vec![(1, 2), (2, 4)], fabric.overlapping_pairs());
+        assert_eq!(Some(3), fabric.non_overlapping_claim());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(3);
+        for _ in 0..1000 {
+            let claim = Claim {
+                owner: rng.next_u64() as usize,
+                rectangle: Rectangle::new(
+                    rng.range(0, i64::MAX / 2),
+                    rng.range(0, i64::MAX / 2),
+                    rng.range(0, i64::MAX / 2) as u64,
+                    rng.range(0, i64::MAX / 2) as u64,
+                ),
+            };
+            let parsed: Claim = claim.to_string().parse().unwrap();
+            assert_eq!(claim.to_string(), parsed.to_string());
+        }
+    }
+}
@@ -1,135 +1,156 @@
-use std::cmp::max;
-use std::env;
-
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
-use util::rectangle::Rectangle;
+use util::random::Rng;
+use util::rectangle;
 
-use adhoc_derive::FromStr;
+use fabric_slicing::{Claim, Fabric};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
-
-    let claims: Vec<Claim> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    let grid = create_grid(&claims);
+    cli::run_with_generator(3, solve, generate);
+}
 
-    println!(
-        "Square inches within two or more claims: {}",
-        grid.count_eq_or_above(2)
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let claims: Vec<Claim> = FileReader::new().read_from_file(input_file)?;
+
+    let overlapping_area = area_covered_at_least(&claims, 2);
+    output.answer(
+        1,
+        overlapping_area,
+        format!(
+            "Square inches within two or more claims: {}",
+            overlapping_area
+        ),
     );
 
-    let non_overlapping_claim = find_non_overlapping_claim(&claims);
-
-    match non_overlapping_claim {
-        Some(id) => println!("Only non-overlapping claim: {}", id),
-        None => println!("No non-overlapping claim found!"),
+    let fabric = Fabric::new(claims);
+    if output.diagnostics_enabled() {
+        output.diagnostic(format!(
+            "Overlapping pairs of claims: {}",
+            fabric.overlapping_pairs().len()
+        ));
     }
-}
-
-fn create_grid(claims: &[Claim]) -> Grid {
-    let (max_width, max_height) = claims
-        .iter()
-        .map(|claim| &claim.rectangle)
-        .map(|rect| (rect.x() + rect.width(), rect.y() + rect.height()))
-        .fold((0, 0), |m, dims| (max(m.0, dims.0), max(m.1, dims.1)));
-    let mut grid = Grid::new(max_width, max_height);
 
-    for claim in claims.iter() {
-        grid.add_rectangle(&claim.rectangle);
+    match fabric.non_overlapping_claim() {
+        Some(id) => output.answer(2, id, format!("Only non-overlapping claim: {}", id)),
+        None => output.diagnostic("No non-overlapping claim found!"),
     }
 
-    grid
+    Ok(())
 }
 
-fn find_non_overlapping_claim(claims: &[Claim]) -> Option<usize> {
-    let mut non_overlapping_claim = None;
+/// Computes the area covered by at least `min_claims` claims.
+fn area_covered_at_least(claims: &[Claim], min_claims: usize) -> u128 {
+    rectangle::covered_area(claims.iter().map(|claim| &claim.rectangle), min_claims)
+}
 
-    for a in claims.iter() {
-        if claims
-            .iter()
-            .filter(|&b| a.owner != b.owner)
-            .all(|b| !a.rectangle.collides_with(&b.rectangle))
-        {
-            non_overlapping_claim = Some(a.owner);
-            break;
-        }
+/// Generates `size` random claims on a 1000x1000 piece of fabric.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for owner in 1..=size {
+        let width = rng.range_usize(1, 30);
+        let height = rng.range_usize(1, 30);
+        let x = rng.range_usize(0, 1000 - width);
+        let y = rng.range_usize(0, 1000 - height);
+        input.push_str(&format!("#{} @ {},{}: {}x{}\n", owner, x, y, width, height));
     }
-
-    non_overlapping_claim
+    input
 }
 
-#[derive(Debug, FromStr)]
-#[adhoc(regex = r"^#(?P<owner>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<width>\d+)x(?P<height>\d+)$")]
-struct Claim {
-    owner: usize,
-    #[adhoc(construct_with = "Rectangle::new(x, y, width, height)")]
-    rectangle: Rectangle,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[derive(Debug)]
-struct Grid {
-    width: usize,
-    height: usize,
-    grid: Vec<u32>,
-}
+    #[test]
+    fn test_part1() {
+        let claims: Vec<Claim> = FileReader::new().read_from_file("input.txt").unwrap();
+        assert_eq!(101781, area_covered_at_least(&claims, 2));
+    }
 
-impl Grid {
-    fn new(width: usize, height: usize) -> Self {
-        Self {
-            width,
-            height,
-            grid: vec![0; width * height],
-        }
+    #[test]
+    fn test_part2() {
+        let claims: Vec<Claim> = FileReader::new().read_from_file("input.txt").unwrap();
+        let non_overlapping_claim = Fabric::new(claims).non_overlapping_claim().unwrap();
+        assert_eq!(909, non_overlapping_claim);
     }
 
-    fn add_rectangle(&mut self, r: &Rectangle) {
-        for x in r.x()..(r.x() + r.width()) {
-            for y in r.y()..(r.y() + r.height()) {
-                self.increment_at(x, y);
-            }
-        }
+    #[test]
+    fn test_example() {
+        let claims: Vec<Claim> = ["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        assert_eq!(4, area_covered_at_least(&claims, 2));
+        assert_eq!(32, area_covered_at_least(&claims, 1));
+        assert_eq!(Some(3), Fabric::new(claims).non_overlapping_claim());
     }
 
-    fn increment_at(&mut self, x: usize, y: usize) {
-        if x >= self.width || y >= self.height {
-            panic!("access out of bounds");
+    #[test]
+    fn test_matches_dense_grid() {
+        let mut rng = Rng::new(3);
+        for _ in 0..20 {
+            let claims: Vec<Claim> = generate(&mut rng, 200)
+                .lines()
+                .map(|l| l.parse().unwrap())
+                .collect();
+
+            let mut grid = vec![0; 1000 * 1000];
+            for r in claims.iter().map(|claim| &claim.rectangle) {
+                for x in r.x()..r.right() {
+                    for y in r.y()..r.bottom() {
+                        grid[(x + y * 1000) as usize] += 1;
+                    }
+                }
+            }
+            for min_claims in 1..4 {
+                let expected = grid.iter().filter(|&&count| count >= min_claims).count() as u128;
+                assert_eq!(expected, area_covered_at_least(&claims, min_claims));
+            }
+
+            let expected = claims
+                .iter()
+                .find(|a| {
+                    claims
+                        .iter()
+                        .filter(|b| a.owner != b.owner)
+                        .all(|b| !a.rectangle.collides_with(&b.rectangle))
+                })
+                .map(|claim| claim.owner);
+            assert_eq!(expected, Fabric::new(claims).non_overlapping_claim());
         }
-        let idx = x + y * self.width;
-        self.grid[idx] = self.grid[idx].saturating_add(1);
     }
 
-    fn count_eq_or_above(&self, val: u32) -> usize {
-        self.grid.iter().filter(|&v| *v >= val).count()
+    #[test]
+    fn test_huge_claim() {
+        let mut claims: Vec<Claim> = (0..20_000)
+            .map(|i| format!("#{} @ {},{}: 1x1", i + 1, i % 200, i / 200))
+            .map(|l| l.parse().unwrap())
+            .collect();
+        claims.push("#20001 @ 0,0: 20000000x20000000".parse().unwrap());
+        assert_eq!(20_000, area_covered_at_least(&claims, 2));
+
+        let fabric = Fabric::new(claims);
+        assert_eq!(20_000, fabric.overlapping(20_001).len());
+        assert_eq!(vec![5, 20_001], fabric.covering(4, 0));
+        assert_eq!(None, fabric.non_overlapping_claim());
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_part1() {
-        let claims: Vec<Claim> = FileReader::new().read_from_file("input.txt").unwrap();
-        let grid = create_grid(&claims);
-        assert_eq!(101781, grid.count_eq_or_above(2));
+    fn test_generate() {
+        let input = generate(&mut Rng::new(1), 50);
+        let claims: Vec<Claim> = input.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(50, claims.len());
+        assert!(claims
+            .iter()
+            .all(|c| c.rectangle.right() <= 1000 && c.rectangle.bottom() <= 1000));
     }
 
     #[test]
-    fn test_part2() {
-        let claims: Vec<Claim> = FileReader::new().read_from_file("input.txt").unwrap();
-        let non_overlapping_claim = find_non_overlapping_claim(&claims).unwrap();
-        assert_eq!(909, non_overlapping_claim);
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(3, solve, generate, size, seed).unwrap();
+            }
+        }
     }
 }
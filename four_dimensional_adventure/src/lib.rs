@@ -0,0 +1,80 @@
+//! Point parser for day 25, shared by the solver and the fuzz targets.
+
+use std::str::FromStr;
+
+use util::input::parse_integers;
+
+/// Point in four-dimensional space-time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Point4D {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+    pub t: isize,
+}
+
+impl Point4D {
+    pub fn new(x: isize, y: isize, z: isize, t: isize) -> Self {
+        Self { x, y, z, t }
+    }
+
+    pub fn manhattan_distance_to(self, other: Point4D) -> usize {
+        (self.x - other.x).unsigned_abs()
+            + (self.y - other.y).unsigned_abs()
+            + (self.z - other.z).unsigned_abs()
+            + (self.t - other.t).unsigned_abs()
+    }
+}
+
+impl std::fmt::Display for Point4D {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.z, self.t)
+    }
+}
+
+impl FromStr for Point4D {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coordinates = parse_integers::<isize>(s).map_err(|e| e.to_string())?;
+        if coordinates.len() != 4 {
+            return Err(format!(
+                "expected 4 coordinates, got {}: {}",
+                coordinates.len(),
+                s
+            ));
+        }
+
+        Ok(Point4D::new(
+            coordinates[0],
+            coordinates[1],
+            coordinates[2],
+            coordinates[3],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Point4D::new(-1, 2, 0, 3), "-1,2,0,+3".parse().unwrap());
+
+        for s in &["", "1,2,3", "1,2,3,4,5", "99999999999999999999,0,0,0"] {
+            assert!(s.parse::<Point4D>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(25);
+        for _ in 0..1000 {
+            let mut value = || rng.range(i64::MIN, i64::MAX) as isize;
+            let point = Point4D::new(value(), value(), value(), value());
+            assert_eq!(point, point.to_string().parse().unwrap());
+        }
+    }
+}
@@ -1,27 +1,24 @@
-use std::env;
-use std::str::FromStr;
-
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
 
+use four_dimensional_adventure::Point4D;
+
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(25, solve);
+}
 
-    let input: Vec<Point4D> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<Point4D> = FileReader::new().read_from_file(input_file)?;
 
     let constellations = find_constellations(&input);
-    println!("Number of constellations: {}", constellations.len());
+    output.answer(
+        1,
+        constellations.len(),
+        format!("Number of constellations: {}", constellations.len()),
+    );
+
+    Ok(())
 }
 
 fn find_constellations(points: &[Point4D]) -> Vec<Constellation> {
@@ -111,48 +108,3 @@ impl Constellation {
         self.points.append(&mut other.points);
     }
 }
-
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct Point4D {
-    x: isize,
-    y: isize,
-    z: isize,
-    t: isize,
-}
-
-impl Point4D {
-    fn new(x: isize, y: isize, z: isize, t: isize) -> Self {
-        Self { x, y, z, t }
-    }
-
-    fn manhattan_distance_to(self, other: Point4D) -> usize {
-        (self.x - other.x).abs() as usize
-            + (self.y - other.y).abs() as usize
-            + (self.z - other.z).abs() as usize
-            + (self.t - other.t).abs() as usize
-    }
-}
-
-impl FromStr for Point4D {
-    type Err = std::num::ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut point = Point4D::new(0, 0, 0, 0);
-        for (i, coord) in s
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<isize>())
-            .enumerate()
-        {
-            match i {
-                0 => point.x = coord?,
-                1 => point.y = coord?,
-                2 => point.z = coord?,
-                3 => point.t = coord?,
-                _ => panic!("invalid input"),
-            }
-        }
-        Ok(point)
-    }
-}
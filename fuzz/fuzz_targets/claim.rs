@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use fabric_slicing::Claim;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Ok(parsed) = s.parse::<Claim>() {
+        let formatted = parsed.to_string();
+        assert_eq!(formatted, formatted.parse::<Claim>().unwrap().to_string());
+    }
+});
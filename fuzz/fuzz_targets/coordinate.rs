@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use chronal_coordinates::Point;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Ok(parsed) = s.parse::<Point>() {
+        assert_eq!(parsed, parsed.to_string().parse::<Point>().unwrap());
+    }
+});
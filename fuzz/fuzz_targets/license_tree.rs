@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use memory_maneuver::Tree;
+
+fuzz_target!(|data: &[u8]| {
+    let numbers: Vec<usize> = data.iter().map(|&b| b as usize % 8).collect();
+    if let Ok(tree) = Tree::parse(&numbers) {
+        assert_eq!(numbers, tree.to_numbers());
+    }
+});
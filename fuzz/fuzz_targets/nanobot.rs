@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use experimental_emergency_teleportation::Nanobot;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Ok(parsed) = s.parse::<Nanobot>() {
+        assert_eq!(parsed, parsed.to_string().parse::<Nanobot>().unwrap());
+    }
+});
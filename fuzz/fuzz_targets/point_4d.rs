@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use four_dimensional_adventure::Point4D;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Ok(parsed) = s.parse::<Point4D>() {
+        assert_eq!(parsed, parsed.to_string().parse::<Point4D>().unwrap());
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use sum_of_its_parts::Instruction;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Ok(parsed) = s.parse::<Instruction>() {
+        assert_eq!(parsed, parsed.to_string().parse::<Instruction>().unwrap());
+    }
+});
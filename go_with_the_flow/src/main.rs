@@ -1,39 +1,31 @@
-use std::env;
-
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 type RegSize = u32;
 const REG_NUMBER: usize = 6;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(19, solve);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
-    let program = parse_input(&input);
+    let program = parse_input(&input).context(ErrorKind::Parse, "Error parsing program")?;
     let mut regs = [0; REG_NUMBER];
     program.execute(&mut regs);
 
-    println!("Value in register 0: {:?}", regs[0]);
+    output.answer(1, regs[0], format!("Value in register 0: {:?}", regs[0]));
 
     /* Part 2, not brute-forceable, only runs with optimized input */
     let mut regs = [0; REG_NUMBER];
     regs[0] = 1;
     program.execute(&mut regs);
 
-    println!("Value in register 0: {:?}", regs[0]);
+    output.answer(2, regs[0], format!("Value in register 0: {:?}", regs[0]));
+
+    Ok(())
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -116,7 +108,7 @@ impl Program {
     }
 }
 
-fn parse_input(input: &[String]) -> Program {
+fn parse_input(input: &[String]) -> Result<Program, String> {
     let mut ip = 0;
     let mut got_ip = false;
 
@@ -125,22 +117,24 @@ fn parse_input(input: &[String]) -> Program {
     for line in input {
         if line.starts_with('#') {
             if got_ip {
-                panic!("Already got an IP!");
+                return Err(String::from("Already got an IP!"));
             }
             ip = line
                 .split_whitespace()
-                .skip(1)
-                .map(|s| s.parse::<RegSize>().unwrap())
-                .nth(0)
-                .unwrap();
-            assert!((ip as usize) < REG_NUMBER);
+                .nth(1)
+                .ok_or_else(|| format!("Missing IP register: {}", line))?
+                .parse::<RegSize>()
+                .map_err(|e| format!("{}: {}", e, line))?;
+            if (ip as usize) >= REG_NUMBER {
+                return Err(format!("Invalid IP register: {}", line));
+            }
             got_ip = true;
         } else if line.starts_with(';') {
             // skip comments
             continue;
         } else {
             let mut iter = line.split_whitespace();
-            let opcode = match iter.next().unwrap() {
+            let opcode = match iter.next().unwrap_or("") {
                 "addr" => Opcode::Addr,
                 "addi" => Opcode::Addi,
                 "mulr" => Opcode::Mulr,
@@ -158,18 +152,24 @@ fn parse_input(input: &[String]) -> Program {
                 "eqri" => Opcode::Eqri,
                 "eqrr" => Opcode::Eqrr,
                 "modr" => Opcode::Modr,
-                s => panic!("Unknown opcode: {}", s),
+                s => return Err(format!("Unknown opcode: {}", s)),
             };
             let mut args: [RegSize; 3] = [0; 3];
             for arg in args.iter_mut() {
-                *arg = iter.next().unwrap().parse::<RegSize>().unwrap();
+                *arg = iter
+                    .next()
+                    .ok_or_else(|| format!("Missing argument: {}", line))?
+                    .parse::<RegSize>()
+                    .map_err(|e| format!("{}: {}", e, line))?;
+            }
+            if iter.next().is_some() {
+                return Err(format!("Too many arguments: {}", line));
             }
-            assert!(iter.next().is_none());
             instructions.push(Instruction { opcode, args });
         }
     }
 
-    Program { ip, instructions }
+    Ok(Program { ip, instructions })
 }
 
 #[cfg(test)]
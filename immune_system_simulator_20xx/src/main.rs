@@ -1,39 +1,79 @@
 use std::collections::HashMap;
-use std::env;
 
 use regex::Regex;
 
+use util::cli::{self, Format, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
+use util::random::Rng;
 
-fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+/// Largest boost tried for part 2. The immune system of the puzzle input needs a boost in the
+/// tens, but if it can't damage some infection group at all, no boost is ever enough.
+const MAX_BOOST: usize = 10_000;
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    let (immune_system, infection) = parse_input(&input);
+fn main() {
+    cli::run_with_generator(24, solve, generate);
+}
 
-    simulate_fight(&mut immune_system.clone(), &mut infection.clone(), 0);
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
+
+    let (immune_system, infection) =
+        parse_input(&input).context(ErrorKind::Parse, "Error parsing armies")?;
+
+    let (_, remaining_units) = simulate_fight(
+        &mut immune_system.clone(),
+        &mut infection.clone(),
+        0,
+        output,
+    );
+    output.answer(
+        1,
+        remaining_units,
+        format!("Remaining units of winning army: {}", remaining_units),
+    );
+
+    let (boost, remaining_units) =
+        find_smallest_boost(&immune_system, &infection, MAX_BOOST, output).ok_or_else(|| {
+            Error::runtime(format!(
+                "The immune system doesn't win with any boost up to {}",
+                MAX_BOOST
+            ))
+        })?;
+    output.answer(
+        2,
+        remaining_units,
+        format!(
+            "Remaining units of immune system with boost {}: {}",
+            boost, remaining_units
+        ),
+    );
+
+    Ok(())
+}
 
-    for boost in 1.. {
-        println!("Boost: {}", boost);
-        if simulate_fight(&mut immune_system.clone(), &mut infection.clone(), boost)
-            == ArmyType::ImmuneSystem
-        {
-            break;
+/// Returns the smallest boost (at most `max_boost`) with which the immune system wins, together
+/// with its remaining units.
+fn find_smallest_boost(
+    immune_system: &[Group],
+    infection: &[Group],
+    max_boost: usize,
+    output: &Output,
+) -> Option<(usize, usize)> {
+    (1..=max_boost).find_map(|boost| {
+        output.diagnostic(format!("Boost: {}", boost));
+        let (winner, remaining_units) = simulate_fight(
+            &mut immune_system.to_vec(),
+            &mut infection.to_vec(),
+            boost,
+            output,
+        );
+        if winner == ArmyType::ImmuneSystem {
+            Some((boost, remaining_units))
+        } else {
+            None
         }
-    }
+    })
 }
 
 fn give_boost(immune_system: &mut [Group], boost: usize) {
@@ -46,7 +86,8 @@ fn simulate_fight(
     immune_system: &mut Vec<Group>,
     infection: &mut Vec<Group>,
     boost: usize,
-) -> ArmyType {
+    output: &Output,
+) -> (ArmyType, usize) {
     if boost > 0 {
         give_boost(immune_system, boost);
     }
@@ -67,16 +108,16 @@ fn simulate_fight(
     let remaining_immune_system = get_remaining_units(&immune_system);
     let remaining_infection = get_remaining_units(&infection);
 
-    println!("Immune system:");
-    println!("Remaining units: {}", remaining_immune_system);
-    println!("Infection:");
-    println!("Remaining units: {}", remaining_infection);
+    output.diagnostic("Immune system:");
+    output.diagnostic(format!("Remaining units: {}", remaining_immune_system));
+    output.diagnostic("Infection:");
+    output.diagnostic(format!("Remaining units: {}", remaining_infection));
 
     if remaining_immune_system > 0 && remaining_infection == 0 {
-        ArmyType::ImmuneSystem
+        (ArmyType::ImmuneSystem, remaining_immune_system)
     } else {
         // let's treat a deadlock as a win for team infection
-        ArmyType::Infection
+        (ArmyType::Infection, remaining_infection)
     }
 }
 
@@ -313,7 +354,7 @@ enum ParseState {
     Infection,
 }
 
-fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
+fn parse_input(input: &[String]) -> Result<(Vec<Group>, Vec<Group>), String> {
     let mut state = ParseState::Start;
     let mut immune_system = Vec::new();
     let mut infection = Vec::new();
@@ -336,7 +377,7 @@ fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
             let captures = match regex.captures(line) {
                 Some(captures) => captures,
                 None => {
-                    panic!("input does not match expected format");
+                    return Err(format!("input does not match expected format: {}", line));
                 }
             };
             let mut values: Vec<&str> = Vec::new();
@@ -347,14 +388,7 @@ fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
                 }
             }
 
-            let attack_type: AttackType = match values[4] {
-                "bludgeoning" => AttackType::Bludgeoning,
-                "slashing" => AttackType::Slashing,
-                "radiation" => AttackType::Radiation,
-                "fire" => AttackType::Fire,
-                "cold" => AttackType::Cold,
-                at => panic!("unknown attack type: {}", at),
-            };
+            let attack_type = parse_attack_type(values[4])?;
 
             let mut weaknesses: Vec<AttackType> = Vec::new();
             let mut immunities: Vec<AttackType> = Vec::new();
@@ -369,14 +403,7 @@ fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
                             .map(|s| s.trim())
                             .filter(|&s| !s.is_empty() && s != "immune" && s != "to")
                     }) {
-                        match s {
-                            "bludgeoning" => immunities.push(AttackType::Bludgeoning),
-                            "slashing" => immunities.push(AttackType::Slashing),
-                            "radiation" => immunities.push(AttackType::Radiation),
-                            "fire" => immunities.push(AttackType::Fire),
-                            "cold" => immunities.push(AttackType::Cold),
-                            at => panic!("unknown attack type: {}", at),
-                        }
+                        immunities.push(parse_attack_type(s)?);
                     }
                 } else if s.starts_with("weak to") {
                     for s in s.split_whitespace().flat_map(|s| {
@@ -384,17 +411,10 @@ fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
                             .map(|s| s.trim())
                             .filter(|&s| !s.is_empty() && s != "weak" && s != "to")
                     }) {
-                        match s {
-                            "bludgeoning" => weaknesses.push(AttackType::Bludgeoning),
-                            "slashing" => weaknesses.push(AttackType::Slashing),
-                            "radiation" => weaknesses.push(AttackType::Radiation),
-                            "fire" => weaknesses.push(AttackType::Fire),
-                            "cold" => weaknesses.push(AttackType::Cold),
-                            at => panic!("unknown attack type: {}", at),
-                        }
+                        weaknesses.push(parse_attack_type(s)?);
                     }
                 } else {
-                    panic!("invalid input {}", s);
+                    return Err(format!("invalid input {}", s));
                 }
             }
 
@@ -408,11 +428,11 @@ fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
                     group_id += 1;
                     group_id - 1
                 },
-                units: values[0].parse::<usize>().unwrap(),
-                hit_points: values[1].parse::<usize>().unwrap(),
-                attack_damage: values[3].parse::<usize>().unwrap(),
+                units: parse_number(values[0])?,
+                hit_points: parse_number(values[1])?,
+                attack_damage: parse_number(values[3])?,
                 attack_type,
-                initiative: values[5].parse::<usize>().unwrap(),
+                initiative: parse_number(values[5])?,
                 weaknesses,
                 immunities,
             };
@@ -423,9 +443,107 @@ fn parse_input(input: &[String]) -> (Vec<Group>, Vec<Group>) {
                 _ => unreachable!(),
             }
         } else {
-            panic!("invalid input");
+            return Err(format!("invalid input: {}", line));
         }
     }
 
-    (immune_system, infection)
+    Ok((immune_system, infection))
+}
+
+/// Generates an immune system and an infection army with `size` groups each, such that the
+/// immune system wins with a boost of at most 100.
+///
+/// Random armies often end in a deadlock whatever the boost, e.g. because the infection is
+/// immune to all attacks of the immune system, so armies are generated until they can be solved.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    // Prints nothing, as no answers are reported
+    let output = Output::new(24, Format::Json, false);
+    loop {
+        let input = generate_armies(rng, size);
+        let lines: Vec<String> = input.lines().map(String::from).collect();
+        let (immune_system, infection) = parse_input(&lines).unwrap();
+        if find_smallest_boost(&immune_system, &infection, 100, &output).is_some() {
+            return input;
+        }
+    }
+}
+
+/// Generates an immune system and an infection army with `size` groups each.
+fn generate_armies(rng: &mut Rng, size: usize) -> String {
+    const ATTACK_TYPES: [&str; 5] = ["bludgeoning", "slashing", "radiation", "fire", "cold"];
+
+    let size = size.max(1);
+    let mut initiatives: Vec<usize> = (1..=2 * size).collect();
+    rng.shuffle(&mut initiatives);
+
+    let mut input = String::new();
+    for (army, initiatives) in ["Immune System", "Infection"]
+        .iter()
+        .zip(initiatives.chunks(size))
+    {
+        input.push_str(&format!("{}:\n", army));
+        for initiative in initiatives {
+            let mut attack_types = ATTACK_TYPES;
+            rng.shuffle(&mut attack_types);
+            let weak = rng.range_usize(0, 3);
+            let immune = rng.range_usize(0, 3);
+            let mut modifiers = Vec::new();
+            if weak > 0 {
+                modifiers.push(format!("weak to {}", attack_types[..weak].join(", ")));
+            }
+            if immune > 0 {
+                modifiers.push(format!(
+                    "immune to {}",
+                    attack_types[weak..weak + immune].join(", ")
+                ));
+            }
+            rng.shuffle(&mut modifiers);
+            let modifiers = if modifiers.is_empty() {
+                String::new()
+            } else {
+                format!("({}) ", modifiers.join("; "))
+            };
+
+            input.push_str(&format!(
+                "{} units each with {} hit points {}with an attack that does {} {} damage at initiative {}\n",
+                rng.range_usize(1, 5000),
+                rng.range_usize(1, 10000),
+                modifiers,
+                rng.range_usize(1, 100),
+                rng.choose(&ATTACK_TYPES).unwrap(),
+                initiative
+            ));
+        }
+        input.push('\n');
+    }
+    input
+}
+
+fn parse_attack_type(s: &str) -> Result<AttackType, String> {
+    match s {
+        "bludgeoning" => Ok(AttackType::Bludgeoning),
+        "slashing" => Ok(AttackType::Slashing),
+        "radiation" => Ok(AttackType::Radiation),
+        "fire" => Ok(AttackType::Fire),
+        "cold" => Ok(AttackType::Cold),
+        at => Err(format!("unknown attack type: {}", at)),
+    }
+}
+
+fn parse_number(s: &str) -> Result<usize, String> {
+    s.parse::<usize>().map_err(|e| format!("{}: {}", e, s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(24, solve, generate, size, seed).unwrap();
+            }
+        }
+    }
 }
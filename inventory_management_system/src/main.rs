@@ -1,85 +1,68 @@
-use std::collections::HashMap;
-use std::env;
-
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
+use util::random::Rng;
+use util::string::{find_similar_pairs, Histogram, Metric};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run_with_generator(2, solve, generate);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
-    println!("Checksum: {}", checksum(&input));
+    let checksum = checksum(&input, &CHECKSUM_MULTIPLICITIES);
+    output.answer(1, checksum, format!("Checksum: {}", checksum));
 
     match find_first_match(&input, 1) {
         Some(idx) => {
-            println!("Boxes: {}, {}", input[idx.0], input[idx.1]);
-            println!(
-                "Common letters: {}",
-                common_letters(&input[idx.0], &input[idx.1])
+            output.diagnostic(format!("Boxes: {}, {}", input[idx.0], input[idx.1]));
+            let common_letters = common_letters(&input[idx.0], &input[idx.1]);
+            output.answer(
+                2,
+                &common_letters,
+                format!("Common letters: {}", common_letters),
             );
         }
-        None => println!("No matches found!"),
+        None => output.diagnostic("No matches found!"),
     }
+
+    Ok(())
 }
 
-fn checksum(inputs: &[String]) -> u64 {
-    let counts = inputs
-        .iter()
-        .map(|input| count_exact_dual_and_triple_occurrences(&input))
-        .fold((0, 0), |mut sum, counts| {
-            if counts.0 > 0 {
-                sum.0 += 1;
-            }
-            if counts.1 > 0 {
-                sum.1 += 1;
+/// Letter multiplicities considered by the checksum.
+const CHECKSUM_MULTIPLICITIES: [usize; 2] = [2, 3];
+
+/// Multiplies, for each of the given multiplicities, the number of IDs containing any letter
+/// exactly that many times.
+fn checksum<S: AsRef<str>>(inputs: &[S], multiplicities: &[usize]) -> u64 {
+    let mut counts = vec![0; multiplicities.len()];
+    for input in inputs {
+        let letters = count_multiplicities(input.as_ref(), multiplicities);
+        for (count, letters) in counts.iter_mut().zip(letters) {
+            if letters > 0 {
+                *count += 1;
             }
-            sum
-        });
+        }
+    }
 
-    counts.0 * counts.1
+    counts.iter().product()
 }
 
-fn count_exact_dual_and_triple_occurrences(input: &str) -> (u32, u32) {
-    // Count number of occurrence of each letter
-    let mut char_count = HashMap::new();
-    for c in input.chars() {
-        let count = char_count.entry(c).or_insert(0u32);
-        *count += 1;
-    }
-
-    // Count number of times a character occurred exactly two or three times
-    char_count.values().fold((0, 0), |mut sum, &v| {
-        if v == 2 {
-            sum.0 += 1;
-        } else if v == 3 {
-            sum.1 += 1;
-        }
-        sum
-    })
+/// Counts, for each of the given multiplicities, the letters occurring exactly that many times.
+fn count_multiplicities(input: &str, multiplicities: &[usize]) -> Vec<usize> {
+    let histogram = Histogram::new(input);
+    multiplicities
+        .iter()
+        .map(|&multiplicity| histogram.with_multiplicity(multiplicity))
+        .collect()
 }
 
 fn find_first_match(inputs: &[String], distance: usize) -> Option<(usize, usize)> {
-    for (i, a) in inputs.iter().enumerate() {
-        for (j, b) in inputs[i + 1..].iter().enumerate() {
-            if util::string::hamming_distance(a, b) == distance {
-                return Some((i, i + j + 1));
-            }
-        }
-    }
-
-    None
+    find_similar_pairs(inputs, distance, Metric::Hamming)
+        .into_iter()
+        .find(|&(_, _, d)| d == distance)
+        .map(|(i, j, _)| (i, j))
 }
 
 fn common_letters(a: &str, b: &str) -> String {
@@ -92,6 +75,25 @@ fn common_letters(a: &str, b: &str) -> String {
     result
 }
 
+/// Generates `size` box IDs, two of which differ by exactly one character.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let letters: Vec<char> = ('a'..='z').collect();
+    let mut ids: Vec<Vec<char>> = (0..size.max(2) - 1)
+        .map(|_| (0..26).map(|_| *rng.choose(&letters).unwrap()).collect())
+        .collect();
+
+    let mut similar = rng.choose(&ids).unwrap().clone();
+    let position = rng.range_usize(0, similar.len());
+    let index = (similar[position] as u8 - b'a') as usize;
+    similar[position] = letters[(index + rng.range_usize(1, 26)) % 26];
+    ids.push(similar);
+    rng.shuffle(&mut ids);
+
+    ids.iter()
+        .map(|id| format!("{}\n", id.iter().collect::<String>()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,13 +109,22 @@ mod tests {
             String::from("abcdee"),
             String::from("ababab"),
         ];
-        assert_eq!((0, 0), count_exact_dual_and_triple_occurrences(&inputs[0]));
-        assert_eq!((1, 1), count_exact_dual_and_triple_occurrences(&inputs[1]));
-        assert_eq!((1, 0), count_exact_dual_and_triple_occurrences(&inputs[2]));
-        assert_eq!((0, 1), count_exact_dual_and_triple_occurrences(&inputs[3]));
-        assert_eq!((2, 0), count_exact_dual_and_triple_occurrences(&inputs[4]));
-        assert_eq!((1, 0), count_exact_dual_and_triple_occurrences(&inputs[5]));
-        assert_eq!((0, 2), count_exact_dual_and_triple_occurrences(&inputs[6]));
+        let counts: Vec<Vec<usize>> = inputs
+            .iter()
+            .map(|input| count_multiplicities(input, &CHECKSUM_MULTIPLICITIES))
+            .collect();
+        assert_eq!(
+            vec![
+                vec![0, 0],
+                vec![1, 1],
+                vec![1, 0],
+                vec![0, 1],
+                vec![2, 0],
+                vec![1, 0],
+                vec![0, 2]
+            ],
+            counts
+        );
     }
 
     #[test]
@@ -127,7 +138,26 @@ mod tests {
             String::from("abcdee"),
             String::from("ababab"),
         ];
-        assert_eq!(12, checksum(&inputs));
+        assert_eq!(12, checksum(&inputs, &CHECKSUM_MULTIPLICITIES));
+    }
+
+    #[test]
+    fn test_checksum_multiplicities() {
+        let inputs = [
+            "abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab",
+        ];
+        assert_eq!(3, checksum(&inputs, &[3]));
+        assert_eq!(4 * 6, checksum(&inputs, &[2, 1]));
+        assert_eq!(0, checksum(&inputs, &[4]));
+
+        // "e" followed by a combining acute accent is a single letter
+        let inputs = [
+            "e\u{301}e\u{301}x",
+            "\u{1F1E8}\u{1F1ED}\u{1F1E8}\u{1F1ED}\u{1F1E8}",
+        ];
+        assert_eq!(vec![1, 0], count_multiplicities(inputs[0], &[2, 3]));
+        assert_eq!(vec![1, 1], count_multiplicities(inputs[1], &[2, 1]));
+        assert_eq!(2, checksum(&inputs, &[2]));
     }
 
     #[test]
@@ -153,7 +183,7 @@ mod tests {
     #[test]
     fn test_part_1() {
         let input: Vec<String> = FileReader::new().read_from_file("input.txt").unwrap();
-        let checksum = checksum(&input);
+        let checksum = checksum(&input, &CHECKSUM_MULTIPLICITIES);
         assert_eq!(6944, checksum);
     }
 
@@ -166,4 +196,22 @@ mod tests {
             common_letters(&input[idx.0], &input[idx.1])
         );
     }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(&mut Rng::new(1), 100);
+        let input: Vec<String> = input.lines().map(String::from).collect();
+        assert_eq!(100, input.len());
+        let idx = find_first_match(&input, 1).unwrap();
+        assert_eq!(25, common_letters(&input[idx.0], &input[idx.1]).len());
+    }
+
+    #[test]
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(2, solve, generate, size, seed).unwrap();
+            }
+        }
+    }
 }
@@ -0,0 +1,33 @@
+//! Benchmarks of the marble game with the puzzle's rules, up to a last marble of 10^8.
+//!
+//! Run with `cargo bench -p marble_mania`. Outside of `cargo bench` (e.g. with
+//! `cargo test --benches`), only the smallest game is played.
+
+use std::time::Instant;
+
+use marble_mania::{high_score, Rules};
+
+const PLAYERS: usize = 430;
+
+fn main() {
+    let full = std::env::args().any(|arg| arg == "--bench");
+    let last_marbles: &[u32] = if full {
+        &[1_000_000, 10_000_000, 100_000_000]
+    } else {
+        &[1_000_000]
+    };
+
+    for &last_marble in last_marbles {
+        let start = Instant::now();
+        let score = high_score(PLAYERS, last_marble, &Rules::default());
+        let elapsed = start.elapsed();
+        println!(
+            "{} players, last marble {:>11}: {:>8.3} s ({:.1} ns/marble, high score {})",
+            PLAYERS,
+            last_marble,
+            elapsed.as_secs_f64(),
+            elapsed.as_nanos() as f64 / last_marble as f64,
+            score
+        );
+    }
+}
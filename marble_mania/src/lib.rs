@@ -0,0 +1,107 @@
+//! Marble game for day 9, shared by the solver and the benchmarks.
+
+use util::circular::CircularList;
+
+/// Rules of the marble game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Marbles that are a multiple of it are scored instead of placed. If it's 0, no marble is
+    /// scored.
+    pub scoring_divisor: u32,
+    /// Position (clockwise) relative to the current marble of the marble that is removed and
+    /// scored together with a scored marble.
+    pub removal_offset: isize,
+    /// Position (clockwise) relative to the current marble of the marble before which a placed
+    /// marble is inserted.
+    pub insertion_offset: isize,
+}
+
+impl Default for Rules {
+    /// Returns the rules of the puzzle.
+    fn default() -> Self {
+        Rules {
+            scoring_divisor: 23,
+            removal_offset: -7,
+            insertion_offset: 2,
+        }
+    }
+}
+
+/// Plays the game with marbles up to `last_marble` and returns the score of every player.
+///
+/// The game starts with marble 0 in the circle; the first player plays marble 1. A placed
+/// marble becomes the current one. After a removal, the marble clockwise of the removed one
+/// becomes the current one. Every move takes constant time (for small offsets).
+pub fn play(players: usize, last_marble: u32, rules: &Rules) -> Vec<u64> {
+    let mut scores = vec![0; players];
+    if players == 0 {
+        return scores;
+    }
+
+    let mut circle = CircularList::with_capacity(last_marble as usize + 1);
+    circle.insert(0);
+    for marble in 1..=last_marble {
+        if rules.scoring_divisor != 0 && marble % rules.scoring_divisor == 0 {
+            circle.move_by(rules.removal_offset);
+            let removed = circle.remove().unwrap_or(0);
+            scores[(marble as usize - 1) % players] += u64::from(marble) + u64::from(removed);
+        } else {
+            circle.move_by(rules.insertion_offset);
+            circle.insert(marble);
+        }
+    }
+
+    scores
+}
+
+/// Plays the game (see `play`) and returns the highest score.
+pub fn high_score(players: usize, last_marble: u32, rules: &Rules) -> u64 {
+    play(players, last_marble, rules)
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game() {
+        let rules = Rules::default();
+        assert_eq!(32, high_score(9, 25, &rules));
+        assert_eq!(8317, high_score(10, 1618, &rules));
+        assert_eq!(146373, high_score(13, 7999, &rules));
+        assert_eq!(2764, high_score(17, 1104, &rules));
+        assert_eq!(54718, high_score(21, 6111, &rules));
+        assert_eq!(37305, high_score(30, 5807, &rules));
+        assert_eq!(0, high_score(0, 25, &rules));
+        assert_eq!(0, high_score(3, 0, &rules));
+    }
+
+    #[test]
+    fn test_rules() {
+        // Marble 4 is scored with marble 2, two positions clockwise of marble 3 in 3 0 2 1
+        let rules = Rules {
+            scoring_divisor: 4,
+            removal_offset: 2,
+            insertion_offset: 2,
+        };
+        assert_eq!(vec![0, 0, 0, 6], play(4, 4, &rules));
+
+        // Every marble scores, removing the only marble in the circle (or none)
+        let rules = Rules {
+            scoring_divisor: 1,
+            removal_offset: -7,
+            insertion_offset: 2,
+        };
+        assert_eq!(vec![1 + 3, 2], play(2, 3, &rules));
+
+        // Without scoring divisor, nothing is ever scored
+        let rules = Rules {
+            scoring_divisor: 0,
+            ..Rules::default()
+        };
+        assert_eq!(vec![0; 3], play(3, 100, &rules));
+    }
+}
@@ -1,137 +1,93 @@
-use std::collections::{HashMap, VecDeque};
-use std::env;
+use std::convert::TryFrom;
 
+use util::cli::{self, Output, SubcommandArgs};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
+use marble_mania::{play, Rules};
 use regex::Regex;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run_with_subcommands(9, solve, None, &[("play", play_command)]);
+}
 
-    let (number_players, last_marble) = {
-        let regex = Regex::new(r"^(\d+)\D+(\d+)\D*$").unwrap();
-        match FileReader::new().parse(regex).read_from_file(input_file) {
-            Ok(input) => input,
-            Err(e) => {
-                println!("Error reading input: {}", e);
-                std::process::exit(1);
-            }
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let (number_players, last_marble) = read_input(input_file)?;
 
-    println!(
+    output.diagnostic(format!(
         "Number of players: {}; Last marble: {}",
         number_players, last_marble
+    ));
+
+    let rules = Rules::default();
+    let high_score = marble_mania::high_score(number_players, last_marble, &rules);
+    output.answer(1, high_score, format!("High score: {}", high_score));
+
+    let last_marble = last_marble
+        .checked_mul(100)
+        .ok_or_else(|| Error::runtime("Last marble too large to be multiplied by 100"))?;
+    let high_score = marble_mania::high_score(number_players, last_marble, &rules);
+    output.answer(
+        2,
+        high_score,
+        format!("High score if last marble were 100x larger: {}", high_score),
     );
 
-    let high_score = play_game(number_players, last_marble);
-    println!("High score: {}", high_score);
-
-    let high_score = play_game(number_players, last_marble * 100);
-    println!("High score if last marble were 100x larger: {}", high_score);
-}
-
-fn play_game(players: usize, last_marble: usize) -> usize {
-    if last_marble <= 2 || players == 0 {
-        return 0;
-    }
-
-    let mut circle: CircularTape<usize> = CircularTape::new();
-    circle.push(0);
-    circle.push(2);
-    circle.push(1);
-    circle.rotate(1); // move 2 into current position
-
-    let mut scores: HashMap<usize, usize> = HashMap::new();
-    let mut current_player = 3;
-
-    // Game is currently in the state:
-    // [2]  0 (2) 1
-
-    for marble in 3..=last_marble {
-        if marble % 23 == 0 {
-            circle.rotate(-7);
-            let score = marble + circle.remove().unwrap();
-
-            *(scores.entry(current_player).or_insert(0)) += score;
-        } else {
-            circle.rotate(2);
-            circle.insert(marble);
-        }
-        current_player = (current_player % players) + 1;
-    }
-
-    match scores.values().max() {
-        Some(score) => *score,
-        None => 0,
-    }
-}
-
-struct CircularTape<T> {
-    deque: VecDeque<T>,
+    Ok(())
 }
 
-impl<T> CircularTape<T> {
-    fn new() -> Self {
-        Self {
-            deque: VecDeque::new(),
-        }
-    }
-
-    fn len(&self) -> usize {
-        self.deque.len()
-    }
-
-    fn push(&mut self, value: T) {
-        self.deque.push_back(value);
-    }
+/// Plays the game with custom rules and prints the winner and all scores.
+///
+/// Usage: `play <input file> [--last-marble <n>] [--divisor <n>] [--removal-offset <n>]
+/// [--insertion-offset <n>]`. Offsets are clockwise from the current marble; by default, the
+/// last marble of the input file and the rules of the puzzle (`--divisor 23 --removal-offset -7
+/// --insertion-offset 2`) are used.
+fn play_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(
+        args,
+        &[],
+        &[
+            "last-marble",
+            "divisor",
+            "removal-offset",
+            "insertion-offset",
+        ],
+    )?;
+    let (number_players, last_marble) = read_input(args.input_file())?;
+    let last_marble = args.value("last-marble")?.unwrap_or(last_marble);
+    let default = Rules::default();
+    let rules = Rules {
+        scoring_divisor: args.value("divisor")?.unwrap_or(default.scoring_divisor),
+        removal_offset: args
+            .value("removal-offset")?
+            .unwrap_or(default.removal_offset),
+        insertion_offset: args
+            .value("insertion-offset")?
+            .unwrap_or(default.insertion_offset),
+    };
 
-    fn insert(&mut self, value: T) {
-        self.deque.push_front(value);
+    let scores = play(number_players, last_marble, &rules);
+    if let Some((winner, high_score)) = scores
+        .iter()
+        .enumerate()
+        .max_by_key(|&(player, &score)| (score, std::cmp::Reverse(player)))
+    {
+        println!("Winner: player {} with {} points", winner + 1, high_score);
     }
-
-    fn remove(&mut self) -> Option<T> {
-        self.deque.pop_front()
+    for (player, score) in scores.iter().enumerate() {
+        println!("Player {}: {}", player + 1, score);
     }
 
-    fn rotate(&mut self, mut amount: isize) {
-        if self.len() < 2 {
-            return;
-        }
-
-        // Amount > 0 <=> rotate counter-clockwise (or left-to-right)
-        while amount > 0 {
-            let front = self.deque.pop_front().unwrap();
-            self.deque.push_back(front);
-            amount -= 1;
-        }
-
-        // Amount < 0 <=> rotate clock-wise (or right-to-left)
-        while amount < 0 {
-            let back = self.deque.pop_back().unwrap();
-            self.deque.push_front(back);
-            amount += 1;
-        }
-    }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_game() {
-        assert_eq!(32, play_game(9, 25));
-        assert_eq!(8317, play_game(10, 1618));
-        assert_eq!(146373, play_game(13, 7999));
-        assert_eq!(2764, play_game(17, 1104));
-        assert_eq!(54718, play_game(21, 6111));
-        assert_eq!(37305, play_game(30, 5807));
-    }
+/// Reads the number of players and the last marble from `input_file`.
+fn read_input(input_file: &str) -> Result<(usize, u32), Error> {
+    let (number_players, last_marble): (usize, usize) = {
+        let regex = Regex::new(r"^(\d+)\D+(\d+)\D*$").unwrap();
+        FileReader::new().parse(regex).read_from_file(input_file)?
+    };
+    let last_marble =
+        u32::try_from(last_marble).context(ErrorKind::Parse, "Invalid last marble")?;
+    Ok((number_players, last_marble))
 }
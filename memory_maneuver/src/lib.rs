@@ -0,0 +1,448 @@
+//! License tree for day 8, shared by the solver and the fuzz targets.
+
+use std::fmt;
+
+/// Error parsing a `Tree` from the numbers of the license file.
+///
+/// Positions are indices into the numbers, and nodes are identified like in `Tree`, by their
+/// position in pre-order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The numbers end within the header of `node`, which starts at `position`.
+    MissingHeader {
+        node: usize,
+        position: usize,
+        available: usize,
+    },
+    /// The numbers end within the metadata entries of `node`, which start at `position`.
+    MissingMetadata {
+        node: usize,
+        position: usize,
+        expected: usize,
+        available: usize,
+    },
+    /// There are `count` numbers after the root node, starting at `position`.
+    TrailingData { position: usize, count: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingHeader {
+                node,
+                position,
+                available,
+            } => write!(
+                f,
+                "node {}: expected header (2 numbers) at position {}, but only {} available",
+                node, position, available
+            ),
+            ParseError::MissingMetadata {
+                node,
+                position,
+                expected,
+                available,
+            } => write!(
+                f,
+                "node {}: expected {} metadata entries at position {}, but only {} available",
+                node, expected, position, available
+            ),
+            ParseError::TrailingData { position, count } => write!(
+                f,
+                "{} unexpected numbers after the root node at position {}",
+                count, position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Node of a `Tree`.
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    parent: Option<usize>,
+    children: Vec<usize>,
+    metadata: Vec<usize>,
+    depth: usize,
+    /// Index after the last node of the subtree of this node.
+    end: usize,
+}
+
+/// Tree of nodes with metadata, as described by the license file.
+///
+/// Nodes are identified by their position in the license file (the root is 0), i.e. in
+/// pre-order. So the subtree of a node consists of the node and the nodes following it, up to
+/// the next node that isn't a descendant. All operations are iterative, so deep trees don't
+/// overflow the stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    nodes: Vec<Node>,
+}
+
+/// Node being parsed: its index and the number of children and metadata entries still to be
+/// read.
+struct Frame {
+    node: usize,
+    children: usize,
+    metadata: usize,
+}
+
+impl Tree {
+    /// Index of the root node.
+    pub const ROOT: usize = 0;
+
+    /// Parses the tree from the numbers of the license file.
+    ///
+    /// # Failures
+    /// Returns an error if the numbers end before the root node is complete, or if there are
+    /// numbers after it.
+    pub fn parse(numbers: &[usize]) -> Result<Self, ParseError> {
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut position = 0;
+
+        loop {
+            let parent = stack.last_mut().map(|frame| {
+                frame.children -= 1;
+                frame.node
+            });
+            let node = nodes.len();
+            if numbers.len() - position < 2 {
+                return Err(ParseError::MissingHeader {
+                    node,
+                    position,
+                    available: numbers.len() - position,
+                });
+            }
+            nodes.push(Node {
+                parent,
+                children: Vec::new(),
+                metadata: Vec::new(),
+                depth: stack.len(),
+                end: 0,
+            });
+            if let Some(parent) = parent {
+                nodes[parent].children.push(node);
+            }
+            stack.push(Frame {
+                node,
+                children: numbers[position],
+                metadata: numbers[position + 1],
+            });
+            position += 2;
+
+            // Finish all nodes whose children are complete
+            while let Some(frame) = stack.last() {
+                if frame.children > 0 {
+                    break;
+                }
+                if numbers.len() - position < frame.metadata {
+                    return Err(ParseError::MissingMetadata {
+                        node: frame.node,
+                        position,
+                        expected: frame.metadata,
+                        available: numbers.len() - position,
+                    });
+                }
+                let end = position + frame.metadata;
+                let subtree_end = nodes.len();
+                let node = &mut nodes[frame.node];
+                node.metadata = numbers[position..end].to_vec();
+                node.end = subtree_end;
+                position = end;
+                stack.pop();
+            }
+            if stack.is_empty() {
+                if position < numbers.len() {
+                    return Err(ParseError::TrailingData {
+                        position,
+                        count: numbers.len() - position,
+                    });
+                }
+                return Ok(Tree { nodes });
+            }
+        }
+    }
+
+    /// Returns the numbers of the license file describing the tree.
+    pub fn to_numbers(&self) -> Vec<usize> {
+        let mut numbers = Vec::new();
+        // Nodes with the number of children already written
+        let mut stack = vec![(Tree::ROOT, 0)];
+        numbers.extend_from_slice(&self.header(Tree::ROOT));
+        while let Some((node, written)) = stack.pop() {
+            let children = self.children(node);
+            if written < children.len() {
+                let child = children[written];
+                stack.push((node, written + 1));
+                stack.push((child, 0));
+                numbers.extend_from_slice(&self.header(child));
+            } else {
+                numbers.extend_from_slice(self.metadata(node));
+            }
+        }
+        numbers
+    }
+
+    fn header(&self, node: usize) -> [usize; 2] {
+        [self.children(node).len(), self.metadata(node).len()]
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the tree has no nodes, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the parent of `node`, or `None` for the root.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.nodes[node].parent
+    }
+
+    /// Returns the children of `node`.
+    pub fn children(&self, node: usize) -> &[usize] {
+        &self.nodes[node].children
+    }
+
+    /// Returns the metadata entries of `node`.
+    pub fn metadata(&self, node: usize) -> &[usize] {
+        &self.nodes[node].metadata
+    }
+
+    /// Returns the depth of `node`, i.e. the number of its ancestors.
+    pub fn depth(&self, node: usize) -> usize {
+        self.nodes[node].depth
+    }
+
+    /// Returns the depth of the deepest node.
+    pub fn height(&self) -> usize {
+        self.nodes.iter().map(|node| node.depth).max().unwrap_or(0)
+    }
+
+    /// Returns the nodes of the subtree of `node` (including itself) in pre-order.
+    pub fn subtree(&self, node: usize) -> std::ops::Range<usize> {
+        node..self.nodes[node].end
+    }
+
+    /// Returns the sum of the metadata entries of `node` and all its descendants. As there are
+    /// fewer than `usize::MAX` entries, the sum fits in a `u128`.
+    pub fn metadata_sum(&self, node: usize) -> u128 {
+        self.nodes[self.subtree(node)]
+            .iter()
+            .flat_map(|node| node.metadata.iter())
+            .map(|&entry| entry as u128)
+            .sum()
+    }
+
+    /// Returns the value of `node`: the sum of its metadata entries if it has no children,
+    /// otherwise the sum of the values of the children referenced (starting with 1) by its
+    /// metadata entries.
+    ///
+    /// Values can grow exponentially with the depth, as children can be referenced repeatedly,
+    /// so `None` is returned if the value doesn't fit in a `u128`.
+    pub fn value(&self, node: usize) -> Option<u128> {
+        let subtree = self.subtree(node);
+        // `None` for values that don't fit, which only matters if they are referenced
+        let mut values = vec![None; subtree.len()];
+        // Children follow their parent, so their values are known when it's reached
+        for i in subtree.clone().rev() {
+            let children = self.children(i);
+            let metadata = self.metadata(i);
+            values[i - subtree.start] = if children.is_empty() {
+                Some(metadata.iter().map(|&entry| entry as u128).sum())
+            } else {
+                metadata
+                    .iter()
+                    .filter(|&&entry| entry != 0 && entry <= children.len())
+                    .try_fold(0u128, |sum, &entry| {
+                        sum.checked_add(values[children[entry - 1] - subtree.start]?)
+                    })
+            };
+        }
+        values[0]
+    }
+
+    /// Returns the path from the root to `node`, as the positions (starting with 0) of the nodes
+    /// on it among the children of their parent.
+    pub fn path(&self, node: usize) -> Vec<usize> {
+        let mut path = Vec::with_capacity(self.depth(node));
+        let mut node = node;
+        while let Some(parent) = self.parent(node) {
+            path.push(
+                self.children(parent)
+                    .iter()
+                    .position(|&c| c == node)
+                    .unwrap(),
+            );
+            node = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns the node at the end of `path` (see `path`), or `None` if there is none.
+    pub fn node_at(&self, path: &[usize]) -> Option<usize> {
+        path.iter().try_fold(Tree::ROOT, |node, &position| {
+            self.children(node).get(position).copied()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [usize; 16] = [2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2];
+
+    #[test]
+    fn test_parse() {
+        let tree = Tree::parse(&EXAMPLE).unwrap();
+        assert_eq!(4, tree.len());
+        assert_eq!(&[1, 2], tree.children(Tree::ROOT));
+        assert_eq!(&[1, 1, 2], tree.metadata(Tree::ROOT));
+        assert_eq!(&[10, 11, 12], tree.metadata(1));
+        assert_eq!(&[3], tree.children(2));
+        assert_eq!(Some(2), tree.parent(3));
+        assert_eq!(None, tree.parent(Tree::ROOT));
+        assert_eq!((2, 2), (tree.depth(3), tree.height()));
+        assert_eq!(2..4, tree.subtree(2));
+
+        assert_eq!(EXAMPLE.to_vec(), tree.to_numbers());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(ParseError::MissingHeader {
+                node: 0,
+                position: 0,
+                available: 0,
+            }),
+            Tree::parse(&[])
+        );
+        assert_eq!(
+            Err(ParseError::MissingHeader {
+                node: 1,
+                position: 2,
+                available: 1,
+            }),
+            Tree::parse(&[1, 1, 0])
+        );
+        assert_eq!(
+            Err(ParseError::MissingMetadata {
+                node: 0,
+                position: 13,
+                expected: 3,
+                available: 2,
+            }),
+            Tree::parse(&EXAMPLE[..15])
+        );
+        assert_eq!(
+            Err(ParseError::MissingMetadata {
+                node: 1,
+                position: 4,
+                expected: usize::MAX,
+                available: 0,
+            }),
+            Tree::parse(&[1, 0, 0, usize::MAX])
+        );
+        assert_eq!(
+            Err(ParseError::MissingHeader {
+                node: 2,
+                position: 5,
+                available: 0,
+            }),
+            Tree::parse(&[usize::MAX, 0, 0, 1, 3])
+        );
+
+        let mut numbers = EXAMPLE.to_vec();
+        numbers.extend_from_slice(&[0, 1]);
+        let error = Tree::parse(&numbers).unwrap_err();
+        assert_eq!(
+            ParseError::TrailingData {
+                position: 16,
+                count: 2,
+            },
+            error
+        );
+        assert_eq!(
+            "2 unexpected numbers after the root node at position 16",
+            error.to_string()
+        );
+        assert_eq!(
+            "node 0: expected 3 metadata entries at position 13, but only 2 available",
+            Tree::parse(&EXAMPLE[..15]).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_sums_and_values() {
+        let tree = Tree::parse(&EXAMPLE).unwrap();
+        assert_eq!(138, tree.metadata_sum(Tree::ROOT));
+        assert_eq!(101, tree.metadata_sum(2));
+        assert_eq!(Some(66), tree.value(Tree::ROOT));
+        assert_eq!(Some(33), tree.value(1));
+        assert_eq!(Some(0), tree.value(2));
+        assert_eq!(Some(99), tree.value(3));
+    }
+
+    #[test]
+    fn test_huge_sums_and_values() {
+        let tree = Tree::parse(&[0, 2, usize::MAX, 1]).unwrap();
+        assert_eq!(usize::MAX as u128 + 1, tree.metadata_sum(Tree::ROOT));
+        assert_eq!(Some(usize::MAX as u128 + 1), tree.value(Tree::ROOT));
+
+        // Every node references its only child twice, doubling the value of the leaf per level
+        let mut numbers = Vec::new();
+        for _ in 0..128 {
+            numbers.extend_from_slice(&[1, 2]);
+        }
+        numbers.extend_from_slice(&[0, 1, 1]);
+        numbers.resize(numbers.len() + 2 * 128, 1);
+        let tree = Tree::parse(&numbers).unwrap();
+        assert_eq!(Some(1 << 127), tree.value(1));
+        assert_eq!(None, tree.value(Tree::ROOT));
+
+        // Values that don't fit only matter if they are referenced
+        let mut numbers = vec![2, 1];
+        numbers.extend_from_slice(&tree.to_numbers());
+        numbers.extend_from_slice(&[0, 1, 5, 2]);
+        let tree = Tree::parse(&numbers).unwrap();
+        assert_eq!(Some(5), tree.value(Tree::ROOT));
+    }
+
+    #[test]
+    fn test_paths() {
+        let tree = Tree::parse(&EXAMPLE).unwrap();
+        for node in 0..tree.len() {
+            assert_eq!(Some(node), tree.node_at(&tree.path(node)));
+        }
+        assert_eq!(vec![1, 0], tree.path(3));
+        assert_eq!(Vec::<usize>::new(), tree.path(Tree::ROOT));
+        assert_eq!(None, tree.node_at(&[2]));
+        assert_eq!(None, tree.node_at(&[0, 0]));
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let depth = 1_000_000;
+        let mut numbers = Vec::new();
+        for _ in 0..depth {
+            numbers.extend_from_slice(&[1, 1]);
+        }
+        numbers.extend_from_slice(&[0, 1, 7]);
+        numbers.resize(numbers.len() + depth, 1);
+
+        let tree = Tree::parse(&numbers).unwrap();
+        assert_eq!(depth, tree.height());
+        assert_eq!(depth as u128 + 7, tree.metadata_sum(Tree::ROOT));
+        assert_eq!(Some(7), tree.value(Tree::ROOT));
+        assert_eq!(depth, tree.path(depth).len());
+        assert_eq!(numbers, tree.to_numbers());
+    }
+}
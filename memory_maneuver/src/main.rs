@@ -1,86 +1,67 @@
-use std::env;
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
+use util::input::FileReader;
+use util::random::Rng;
 
-use util::input::{FileReader, FromFile};
+use memory_maneuver::Tree;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
-
-    let input: String = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    // Collect to Vec<usize>
-    let input: Vec<usize> = if let Ok(v) = input
-        .split_whitespace()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<usize>())
-        .collect()
-    {
-        v
-    } else {
-        println!("Could not parse input!");
-        std::process::exit(1);
-    };
-
-    let (sum_of_metadata, root_value) = match sum_metadata(&input) {
-        Ok((sum, root_value, _)) => (sum, root_value),
-        Err(e) => {
-            println!("Error summing metadata: {}", e);
-            std::process::exit(1);
-        }
-    };
-    println!("Sum of metadata: {}", sum_of_metadata);
-    println!("Value of root node: {}", root_value);
+    cli::run_with_generator(8, solve, generate);
 }
 
-fn sum_metadata(input: &[usize]) -> Result<(usize, usize, &[usize]), String> {
-    if input.len() < 2 {
-        return Err(String::from("Expected header, got end of data"));
-    }
-
-    let number_child_nodes = input[0];
-    let number_metadata_entries = input[1];
-
-    let mut input = &input[2..];
-    let mut sum = 0;
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<usize> = FileReader::new()
+        .separator(' ')
+        .read_separated(input_file)?;
+
+    let tree = Tree::parse(&input).context(ErrorKind::Parse, "Error parsing license tree")?;
+
+    let sum_of_metadata = tree.metadata_sum(Tree::ROOT);
+    output.answer(
+        1,
+        sum_of_metadata,
+        format!("Sum of metadata: {}", sum_of_metadata),
+    );
+    let root_value = tree
+        .value(Tree::ROOT)
+        .ok_or_else(|| Error::runtime("Value of root node exceeds the range of u128"))?;
+    output.answer(2, root_value, format!("Value of root node: {}", root_value));
+
+    Ok(())
+}
 
-    let mut values: Vec<usize> = Vec::new();
-    for _ in 0..number_child_nodes {
-        let res = sum_metadata(&input)?;
-        sum += res.0;
-        values.push(res.1);
-        input = res.2;
+/// Generates a tree consisting of at most `size` nodes.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = Vec::new();
+    let mut remaining = size.max(1) - 1;
+    // Nodes with the number of children still to be generated and of metadata entries
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    loop {
+        let number_child_nodes = rng.range_usize(0, 5).min(remaining);
+        let number_metadata_entries = rng.range_usize(1, 4);
+        remaining -= number_child_nodes;
+        numbers.push(number_child_nodes);
+        numbers.push(number_metadata_entries);
+        stack.push((number_child_nodes, number_metadata_entries));
+
+        // Finish all nodes whose children are complete
+        while let Some((children, metadata)) = stack.last_mut() {
+            if *children > 0 {
+                *children -= 1;
+                break;
+            }
+            for _ in 0..*metadata {
+                numbers.push(rng.range_usize(1, 10));
+            }
+            stack.pop();
+        }
+        if stack.is_empty() {
+            break;
+        }
     }
 
-    let sum_of_metadata = input[..number_metadata_entries].iter().sum::<usize>();
-    sum += sum_of_metadata;
-
-    let node_value = if values.is_empty() {
-        sum_of_metadata
-    } else {
-        input[..number_metadata_entries]
-            .iter()
-            .fold(0, |mut sum, &metadata| {
-                if metadata != 0 && metadata <= values.len() {
-                    sum += values[metadata - 1];
-                }
-
-                sum
-            })
-    };
-
-    Ok((sum, node_value, &input[number_metadata_entries..]))
+    let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    format!("{}\n", numbers.join(" "))
 }
 
 #[cfg(test)]
@@ -90,14 +71,35 @@ mod tests {
     #[test]
     fn test_sum_metadata() {
         let input = vec![2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2];
-        let (sum, _, _) = sum_metadata(&input).unwrap();
-        assert_eq!(138, sum);
+        let tree = Tree::parse(&input).unwrap();
+        assert_eq!(138, tree.metadata_sum(Tree::ROOT));
     }
 
     #[test]
     fn test_node_value() {
         let input = vec![2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2];
-        let (_, root_value, _) = sum_metadata(&input).unwrap();
-        assert_eq!(66, root_value);
+        let tree = Tree::parse(&input).unwrap();
+        assert_eq!(Some(66), tree.value(Tree::ROOT));
+    }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(&mut Rng::new(1), 100);
+        let input: Vec<usize> = input
+            .split_whitespace()
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let tree = Tree::parse(&input).unwrap();
+        assert!(tree.len() <= 100);
+        assert_eq!(input, tree.to_numbers());
+    }
+
+    #[test]
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(8, solve, generate, size, seed).unwrap();
+            }
+        }
     }
 }
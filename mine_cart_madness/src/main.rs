@@ -1,52 +1,53 @@
 use std::collections::HashMap;
-use std::env;
 
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(13, solve);
+}
 
-    let input: String = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: String = FileReader::new().read_from_file(input_file)?;
 
     // TODO: only necessary because FileReader trims by default...
-    let input: Vec<String> = input.lines().map(|s| s.parse().unwrap()).collect();
+    let input: Vec<String> = input.lines().map(|s| s.to_string()).collect();
 
-    let mut grid = Grid::create(&input);
-    run_simulation(&mut grid, false);
+    let mut grid = Grid::create(&input).context(ErrorKind::Parse, "Error reading tracks")?;
+    report_outcome(1, run_simulation(&mut grid, false), output);
 
-    let mut grid = Grid::create(&input);
-    run_simulation(&mut grid, true);
+    let mut grid = Grid::create(&input).context(ErrorKind::Parse, "Error reading tracks")?;
+    report_outcome(2, run_simulation(&mut grid, true), output);
+
+    Ok(())
 }
 
-fn run_simulation(grid: &mut Grid, remove_collisions: bool) {
+fn run_simulation(grid: &mut Grid, remove_collisions: bool) -> Outcome {
     loop {
         let outcome = grid.move_carts(remove_collisions);
-        match outcome {
-            Outcome::Running => continue,
-            Outcome::Collision(collision) => {
-                println!("Collision at ({},{})", collision.0, collision.1);
-                break;
-            }
-            Outcome::LastCartStanding(position) => {
-                println!("Last cart standing ({},{})", position.0, position.1);
-                break;
-            }
+        if outcome != Outcome::Running {
+            return outcome;
         }
     }
 }
 
+fn report_outcome(part: u8, outcome: Outcome, output: &Output) {
+    match outcome {
+        Outcome::Running => {}
+        Outcome::Collision(collision) => output.answer(
+            part,
+            format!("{},{}", collision.0, collision.1),
+            format!("Collision at ({},{})", collision.0, collision.1),
+        ),
+        Outcome::LastCartStanding(position) => output.answer(
+            part,
+            format!("{},{}", position.0, position.1),
+            format!("Last cart standing ({},{})", position.0, position.1),
+        ),
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum Outcome {
     Running,
@@ -171,7 +172,7 @@ struct Grid {
 }
 
 impl Grid {
-    fn create(input: &[String]) -> Self {
+    fn create(input: &[String]) -> Result<Self, String> {
         let (width, height) = if !input.is_empty() {
             (input[0].len(), input.len())
         } else {
@@ -186,7 +187,7 @@ impl Grid {
         let mut shadowed_cells = HashMap::new();
         let mut carts = Vec::new();
 
-        input.iter().flat_map(|s| s.chars()).for_each(|c| {
+        for c in input.iter().flat_map(|s| s.chars()) {
             let cell = match c {
                 ' ' => Cell::Empty,
                 '|' => Cell::VerticalTrack,
@@ -246,7 +247,7 @@ impl Grid {
                     carts.push(cart);
                     Cell::Cart(cart)
                 }
-                c => panic!("unexpected input: {}!", c),
+                c => return Err(format!("unexpected input: {}!", c)),
             };
             grid.push(cell);
 
@@ -255,15 +256,15 @@ impl Grid {
                 y += 1;
                 x = 0;
             }
-        });
+        }
 
-        Self {
+        Ok(Self {
             width,
             height,
             grid,
             shadowed_cells,
             carts,
-        }
+        })
     }
 
     fn move_carts(&mut self, remove_collisions: bool) -> Outcome {
@@ -1,49 +1,51 @@
 use std::collections::VecDeque;
-use std::env;
 
-use util::input::{FileReader, FromFile};
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
+use util::input::{FileReader, FromFile, KeyValues};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
-
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    let depth = input[0]
-        .split("depth: ")
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<usize>().unwrap())
-        .nth(0)
-        .unwrap();
+    cli::run(22, solve);
+}
 
-    let mut target = input[1]
-        .split("target: ")
-        .flat_map(|s| s.split(','))
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<usize>().unwrap());
-    let target = (target.next().unwrap(), target.next().unwrap());
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: KeyValues = FileReader::new().read_from_file(input_file)?;
+
+    let depth: usize = input
+        .get("depth")
+        .context(ErrorKind::Parse, "Error reading depth")?;
+
+    let target = input
+        .get_list::<usize>("target")
+        .context(ErrorKind::Parse, "Error reading target")?;
+    if target.len() != 2 {
+        return Err(Error::parse(format!(
+            "Expected target with 2 coordinates, got {}",
+            target.len()
+        )));
+    }
+    let target = (target[0], target[1]);
 
-    println!("depth = {}", depth);
-    println!("target = ({},{})", target.0, target.1);
+    output.diagnostic(format!("depth = {}", depth));
+    output.diagnostic(format!("target = ({},{})", target.0, target.1));
 
     let grid = Grid::new(depth, target, (target.0 + 10, target.1 + 10));
     let total_risk_level = grid.total_risk_level(target);
-    println!("Total risk level: {}", total_risk_level);
+    output.answer(
+        1,
+        total_risk_level,
+        format!("Total risk level: {}", total_risk_level),
+    );
 
     let map = grid.to_map();
     let shortest_path = map.shortest_path((0, 0), target);
-    println!("Shortest path: {} minutes", shortest_path);
+    output.answer(
+        2,
+        shortest_path,
+        format!("Shortest path: {} minutes", shortest_path),
+    );
+
+    Ok(())
 }
 
 struct Grid {
@@ -0,0 +1,99 @@
+//! Record parser for day 4, shared by the solver and the fuzz targets.
+
+use chrono::naive::NaiveDateTime;
+
+use adhoc_derive::FromStr;
+
+/// Entry of a guard record.
+#[derive(Debug, PartialEq, FromStr)]
+pub enum Entry {
+    #[adhoc(regex = r"^Guard #(?P<0>\d+) begins shift$")]
+    ShiftBegin(usize),
+    #[adhoc(regex = r"^falls asleep$")]
+    FallAsleep,
+    #[adhoc(regex = r"^wakes up$")]
+    WakeUp,
+}
+
+/// Timestamped guard record.
+#[derive(Debug, PartialEq, FromStr)]
+#[adhoc(regex = r"^\[(?P<timestamp>.+)\] (?P<entry>.+)$")]
+pub struct Record {
+    #[adhoc(
+        construct_with = r#"NaiveDateTime::parse_from_str(timestamp: &str, "%Y-%m-%d %H:%M")?"#
+    )]
+    pub timestamp: NaiveDateTime,
+    pub entry: Entry,
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::ShiftBegin(id) => write!(f, "Guard #{} begins shift", id),
+            Entry::FallAsleep => write!(f, "falls asleep"),
+            Entry::WakeUp => write!(f, "wakes up"),
+        }
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}",
+            self.timestamp.format("%Y-%m-%d %H:%M"),
+            self.entry
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::naive::NaiveDate;
+    use util::random::Rng;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Record {
+                timestamp: NaiveDate::from_ymd_opt(1518, 11, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 5, 0)
+                    .unwrap(),
+                entry: Entry::ShiftBegin(10),
+            },
+            "[1518-11-01 00:05] Guard #10 begins shift".parse().unwrap()
+        );
+
+        for s in &[
+            "",
+            "[1518-11-01 00:05] Guard # begins shift",
+            "[1518-11-01 00:05] Guard #-10 begins shift",
+            "[1518-13-01 00:05] falls asleep",
+            "[1518-11-01 24:05] wakes up",
+            "[1518-11-01 00:05] wakes up later",
+            "1518-11-01 00:05 wakes up",
+        ] {
+            assert!(s.parse::<Record>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(4);
+        let first_day = NaiveDate::from_ymd_opt(1518, 1, 1).unwrap();
+        for _ in 0..1000 {
+            let timestamp = (first_day + chrono::Duration::days(rng.range(0, 365)))
+                .and_hms_opt(rng.range(0, 24) as u32, rng.range(0, 60) as u32, 0)
+                .unwrap();
+            let entry = match rng.range_usize(0, 3) {
+                0 => Entry::ShiftBegin(rng.next_u64() as usize),
+                1 => Entry::FallAsleep,
+                _ => Entry::WakeUp,
+            };
+            let record = Record { timestamp, entry };
+            assert_eq!(record, record.to_string().parse().unwrap());
+        }
+    }
+}
@@ -1,79 +1,446 @@
 use std::collections::HashMap;
-use std::env;
+use std::fmt::Write;
 
-use chrono::naive::NaiveDateTime;
-use chrono::Timelike;
+use chrono::naive::{NaiveDate, NaiveDateTime};
+use chrono::{Duration, Timelike};
 
+use util::cli::{self, Format, Output, SubcommandArgs};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
+use util::random::Rng;
 
-use adhoc_derive::FromStr;
+use repose_record::{Entry, Record};
+
+/// Minutes of the midnight hour, during which guards may fall asleep.
+const MINUTES: usize = 60;
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run_with_subcommands(
+        4,
+        solve,
+        Some(generate),
+        &[("report", report), ("repair", repair)],
+    );
+}
 
-    let mut records: Vec<Record> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let records = read_records(input_file)?;
+    let shifts = validated_shifts(&records, false)?;
+    print_answers(&shifts, output)
+}
 
-    records.sort_unstable_by_key(|r| r.timestamp);
-    let records = records;
+/// Solves the puzzle like the default command, but repairs problems in the log instead of
+/// rejecting it.
+///
+/// Usage: `repair <input file> [--format text|json] [--diagnostics]`. The problems found are
+/// reported as warnings on stderr.
+fn repair(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &["diagnostics"], &["format"])?;
+    let format = args.value::<Format>("format")?.unwrap_or(Format::Text);
+    let output = Output::new(4, format, args.flag("diagnostics"));
+    let records = read_records(args.input_file())?;
+    let shifts = validated_shifts(&records, true)?;
+    print_answers(&shifts, &output)
+}
 
-    let sleep_distributions = create_distributions(&records);
+/// Prints the answers to both parts for `shifts`, or returns an error if there are none.
+fn print_answers(shifts: &[Shift], output: &Output) -> Result<(), Error> {
+    if shifts.is_empty() {
+        return Err(Error::parse("no shifts with a guard"));
+    }
+    let sleep_distributions = create_distributions(shifts);
 
     let (guard_most_asleep, asleep_time) = find_guard_most_minutes_asleep(&sleep_distributions);
     let minute_asleep_most = sleep_distributions[&guard_most_asleep].minute_most_asleep();
 
-    println!(
-        "Guard most asleep: {} => was asleep for {} minutes (most at minute {}) => Result: {}",
-        guard_most_asleep,
-        asleep_time,
-        minute_asleep_most,
-        guard_most_asleep * minute_asleep_most
+    output.answer(
+        1,
+        guard_most_asleep * minute_asleep_most,
+        format!(
+            "Guard most asleep: {} => was asleep for {} minutes (most at minute {}) => Result: {}",
+            guard_most_asleep,
+            asleep_time,
+            minute_asleep_most,
+            guard_most_asleep * minute_asleep_most
+        ),
     );
 
     let (guard_most_asleep_at_same_minute, minute_asleep_most) =
         find_guard_most_asleep_at_same_minute(&sleep_distributions);
 
-    println!(
-        "Guard most asleep at single minute: {} @ minute {} => Result: {}",
-        guard_most_asleep_at_same_minute,
-        minute_asleep_most,
-        guard_most_asleep_at_same_minute * minute_asleep_most
+    output.answer(
+        2,
+        guard_most_asleep_at_same_minute * minute_asleep_most,
+        format!(
+            "Guard most asleep at single minute: {} @ minute {} => Result: {}",
+            guard_most_asleep_at_same_minute,
+            minute_asleep_most,
+            guard_most_asleep_at_same_minute * minute_asleep_most
+        ),
     );
+
+    Ok(())
 }
 
-fn create_distributions(records: &[Record]) -> HashMap<usize, SleepDistribution> {
-    let mut distributions: HashMap<usize, SleepDistribution> = HashMap::new();
+/// Prints a report on the shifts in the input file.
+///
+/// Usage: `report <input file> [--table guards|nights] [--csv] [--repair]`. The `guards` table
+/// (default) lists the number of shifts and the total time asleep of every guard, together with
+/// how often they were asleep at each minute; the `nights` table shows when the guard on duty was
+/// asleep during each night. Problems in the log are only repaired if `--repair` is given.
+fn report(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &["csv", "repair"], &["table"])?;
+    let table = args.value::<String>("table")?;
+    let records = read_records(args.input_file())?;
+    let shifts = validated_shifts(&records, args.flag("repair"))?;
+
+    let csv = args.flag("csv");
+    let report = match table.as_deref().unwrap_or("guards") {
+        "guards" if csv => guards_csv(&shifts),
+        "guards" => guards_table(&shifts),
+        "nights" if csv => nights_csv(&shifts),
+        "nights" => nights_table(&shifts),
+        table => {
+            return Err(Error::usage(format!(
+                "Unknown table \"{}\" (expected \"guards\" or \"nights\")",
+                table
+            )))
+        }
+    };
+    print!("{}", report);
+
+    Ok(())
+}
+
+/// Reads the records from `input_file`, sorted by time. Records with the same timestamp keep
+/// their order from the input.
+fn read_records(input_file: &str) -> Result<Vec<Record>, Error> {
+    let mut records: Vec<Record> = FileReader::new().read_from_file(input_file)?;
+    records.sort_by_key(|r| r.timestamp);
+    Ok(records)
+}
+
+/// Sleep pattern of a guard during a single shift.
+struct Shift {
+    /// Date of the midnight hour covered by the shift.
+    date: NaiveDate,
+    guard: usize,
+    asleep: [bool; MINUTES],
+}
+
+impl Shift {
+    /// Returns the start of the midnight hour covered by the shift.
+    fn midnight(&self) -> NaiveDateTime {
+        self.date.and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    /// Marks the guard as asleep from minute `from` until (excluding) minute `until`.
+    fn sleep(&mut self, from: usize, until: usize) {
+        for asleep in self.asleep[from..until].iter_mut() {
+            *asleep = true;
+        }
+    }
+
+    fn minutes_asleep(&self) -> usize {
+        self.asleep.iter().filter(|&&asleep| asleep).count()
+    }
+}
+
+/// Problem found in the log of guard records.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum IssueKind {
+    /// Guard falls asleep or wakes up, but no shift has begun that night.
+    NoGuard,
+    /// Guard falls asleep while already asleep. Repaired by ignoring the record.
+    AlreadyAsleep,
+    /// Guard wakes up without being asleep. Repaired by ignoring the record.
+    NotAsleep,
+    /// Guard falls asleep, but doesn't wake up before the end of the shift. Repaired by letting
+    /// the guard sleep until the end of the midnight hour.
+    NeverWakesUp,
+    /// Guard falls asleep or wakes up outside the midnight hour. Repaired by moving the record to
+    /// the start or end of the midnight hour.
+    OutsideMidnightHour,
+}
 
-    let mut current_guard_id = 0;
-    let mut fall_asleep_time = 0;
-    for record in records.iter() {
-        match record.entry {
-            Entry::ShiftBegin(id) => current_guard_id = id,
-            Entry::FallAsleep => fall_asleep_time = record.timestamp.time().minute(),
-            Entry::WakeUp => {
-                let wake_up = record.timestamp.time().minute();
-                distributions
-                    .entry(current_guard_id)
-                    .or_insert_with(SleepDistribution::new)
-                    .increment_asleep(fall_asleep_time as usize, wake_up as usize);
+impl std::fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IssueKind::NoGuard => write!(f, "no guard is on duty"),
+            IssueKind::AlreadyAsleep => write!(f, "guard is already asleep"),
+            IssueKind::NotAsleep => write!(f, "guard is not asleep"),
+            IssueKind::NeverWakesUp => write!(f, "guard never wakes up"),
+            IssueKind::OutsideMidnightHour => write!(f, "outside of the midnight hour"),
+        }
+    }
+}
+
+/// Problem found in the log, referring to the offending record by its index.
+#[derive(Debug, PartialEq, Eq)]
+struct Issue {
+    record: usize,
+    kind: IssueKind,
+}
+
+/// Splits the (sorted) `records` into shifts, repairing any problems found in the log. Returns
+/// the shifts together with the problems, sorted by record.
+fn create_shifts(records: &[Record]) -> (Vec<Shift>, Vec<Issue>) {
+    let mut shifts: Vec<Shift> = Vec::new();
+    let mut issues = Vec::new();
+
+    // Minute the guard on duty fell asleep at, and the corresponding record
+    let mut asleep_since: Option<(usize, usize)> = None;
+    for (index, record) in records.iter().enumerate() {
+        let guard = match record.entry {
+            Entry::ShiftBegin(guard) => guard,
+            Entry::FallAsleep | Entry::WakeUp => {
+                let shift = match shifts.last_mut() {
+                    Some(shift) if record.timestamp < shift.midnight() + Duration::days(1) => shift,
+                    _ => {
+                        issues.push(Issue {
+                            record: index,
+                            kind: IssueKind::NoGuard,
+                        });
+                        continue;
+                    }
+                };
+
+                let minute = (record.timestamp - shift.midnight()).num_minutes();
+                if !(0..MINUTES as i64).contains(&minute) {
+                    issues.push(Issue {
+                        record: index,
+                        kind: IssueKind::OutsideMidnightHour,
+                    });
+                }
+                let minute = minute.clamp(0, MINUTES as i64) as usize;
+
+                match (&record.entry, asleep_since) {
+                    (Entry::FallAsleep, None) => asleep_since = Some((minute, index)),
+                    (Entry::FallAsleep, Some(_)) => issues.push(Issue {
+                        record: index,
+                        kind: IssueKind::AlreadyAsleep,
+                    }),
+                    (_, Some((since, _))) => {
+                        shift.sleep(since, minute);
+                        asleep_since = None;
+                    }
+                    (_, None) => issues.push(Issue {
+                        record: index,
+                        kind: IssueKind::NotAsleep,
+                    }),
+                }
+                continue;
             }
+        };
+
+        if let Some((since, fell_asleep)) = asleep_since.take() {
+            issues.push(Issue {
+                record: fell_asleep,
+                kind: IssueKind::NeverWakesUp,
+            });
+            if let Some(shift) = shifts.last_mut() {
+                shift.sleep(since, MINUTES);
+            }
+        }
+
+        // Shifts beginning before midnight cover the next day's midnight hour
+        let mut date = record.timestamp.date();
+        if record.timestamp.time().hour() != 0 {
+            date += Duration::days(1);
         }
+        shifts.push(Shift {
+            date,
+            guard,
+            asleep: [false; MINUTES],
+        });
+    }
+
+    if let Some((since, fell_asleep)) = asleep_since {
+        issues.push(Issue {
+            record: fell_asleep,
+            kind: IssueKind::NeverWakesUp,
+        });
+        if let Some(shift) = shifts.last_mut() {
+            shift.sleep(since, MINUTES);
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.record);
+    (shifts, issues)
+}
+
+/// Splits the (sorted) `records` into shifts. If the log contains problems, they are either
+/// repaired (if `repair` is set) and reported as warnings on stderr, or returned as error
+/// listing the offending records.
+fn validated_shifts(records: &[Record], repair: bool) -> Result<Vec<Shift>, Error> {
+    let (shifts, issues) = create_shifts(records);
+    if issues.is_empty() {
+        return Ok(shifts);
+    }
+
+    let descriptions: Vec<String> = issues
+        .iter()
+        .map(|issue| format!("{}: {}", records[issue.record], issue.kind))
+        .collect();
+    if repair {
+        for description in descriptions {
+            eprintln!("Warning: {} (repaired)", description);
+        }
+        Ok(shifts)
+    } else {
+        Err(Error::parse(format!(
+            "Invalid guard log ({} problems, run the repair subcommand to repair them):\n  {}",
+            issues.len(),
+            descriptions.join("\n  ")
+        )))
+    }
+}
+
+fn create_distributions(shifts: &[Shift]) -> HashMap<usize, SleepDistribution> {
+    let mut distributions: HashMap<usize, SleepDistribution> = HashMap::new();
+
+    for shift in shifts {
+        distributions
+            .entry(shift.guard)
+            .or_insert_with(SleepDistribution::new)
+            .add_shift(shift);
     }
 
     distributions
 }
 
+/// Returns the guards together with their number of shifts and sleep distribution, sorted by ID.
+fn summarize_guards(shifts: &[Shift]) -> Vec<(usize, usize, SleepDistribution)> {
+    let mut guards: HashMap<usize, (usize, SleepDistribution)> = HashMap::new();
+    for shift in shifts {
+        let (count, distribution) = guards
+            .entry(shift.guard)
+            .or_insert_with(|| (0, SleepDistribution::new()));
+        *count += 1;
+        distribution.add_shift(shift);
+    }
+
+    let mut guards: Vec<_> = guards
+        .into_iter()
+        .map(|(guard, (count, distribution))| (guard, count, distribution))
+        .collect();
+    guards.sort_unstable_by_key(|&(guard, _, _)| guard);
+    guards
+}
+
+/// Returns the two header lines of a table with a column per minute, indented by `indent`.
+fn minute_header(indent: usize) -> String {
+    let tens: String = (0..MINUTES)
+        .map(|m| (b'0' + (m / 10) as u8) as char)
+        .collect();
+    let ones: String = (0..MINUTES)
+        .map(|m| (b'0' + (m % 10) as u8) as char)
+        .collect();
+    format!(
+        "{:indent$}{}\n{:indent$}{}\n",
+        "",
+        tens,
+        "",
+        ones,
+        indent = indent
+    )
+}
+
+fn guards_table(shifts: &[Shift]) -> String {
+    let mut table = format!(
+        "{:<8}{:>6}{:>8}{:>6}  Minute\n",
+        "Guard", "Shifts", "Asleep", "Most"
+    );
+    table.push_str(&minute_header(30));
+    for (guard, count, distribution) in summarize_guards(shifts) {
+        // Number of nights asleep per minute, '+' for ten or more
+        let histogram: String = distribution
+            .minutes
+            .iter()
+            .map(|&n| match n {
+                0 => '.',
+                1..=9 => (b'0' + n as u8) as char,
+                _ => '+',
+            })
+            .collect();
+        let _ = writeln!(
+            table,
+            "{:<8}{:>6}{:>8}{:>6}  {}",
+            format!("#{}", guard),
+            count,
+            distribution.sum(),
+            distribution.minute_most_asleep(),
+            histogram
+        );
+    }
+    table
+}
+
+fn guards_csv(shifts: &[Shift]) -> String {
+    let mut csv = String::from("guard,shifts,minutes_asleep,minute_most_asleep");
+    for minute in 0..MINUTES {
+        let _ = write!(csv, ",{:02}", minute);
+    }
+    csv.push('\n');
+    for (guard, count, distribution) in summarize_guards(shifts) {
+        let _ = write!(
+            csv,
+            "{},{},{},{}",
+            guard,
+            count,
+            distribution.sum(),
+            distribution.minute_most_asleep()
+        );
+        for n in distribution.minutes.iter() {
+            let _ = write!(csv, ",{}", n);
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+fn nights_table(shifts: &[Shift]) -> String {
+    let mut table = format!("{:<7}{:<8}Minute\n", "Date", "ID");
+    table.push_str(&minute_header(15));
+    for shift in shifts {
+        let timeline: String = shift
+            .asleep
+            .iter()
+            .map(|&asleep| if asleep { '#' } else { '.' })
+            .collect();
+        let _ = writeln!(
+            table,
+            "{:<7}{:<8}{}",
+            shift.date.format("%m-%d").to_string(),
+            format!("#{}", shift.guard),
+            timeline
+        );
+    }
+    table
+}
+
+fn nights_csv(shifts: &[Shift]) -> String {
+    let mut csv = String::from("date,guard,minutes_asleep");
+    for minute in 0..MINUTES {
+        let _ = write!(csv, ",{:02}", minute);
+    }
+    csv.push('\n');
+    for shift in shifts {
+        let _ = write!(
+            csv,
+            "{},{},{}",
+            shift.date.format("%Y-%m-%d"),
+            shift.guard,
+            shift.minutes_asleep()
+        );
+        for &asleep in shift.asleep.iter() {
+            let _ = write!(csv, ",{}", asleep as u8);
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
 fn find_guard_most_minutes_asleep(
     sleep_distributions: &HashMap<usize, SleepDistribution>,
 ) -> (usize, u32) {
@@ -100,25 +467,26 @@ fn find_guard_most_asleep_at_same_minute(
 }
 
 struct SleepDistribution {
-    minutes: [u32; 60],
+    minutes: [u32; MINUTES],
 }
 
 impl SleepDistribution {
     fn new() -> Self {
-        Self { minutes: [0; 60] }
+        Self {
+            minutes: [0; MINUTES],
+        }
     }
 
     fn at(&self, minute: usize) -> u32 {
-        assert!(minute < 60);
+        assert!(minute < MINUTES);
         self.minutes[minute]
     }
 
-    fn increment_asleep(&mut self, from: usize, until: usize) {
-        assert!(from <= until);
-        assert!(until < 60);
-
-        for min in from..until {
-            self.minutes[min] += 1;
+    fn add_shift(&mut self, shift: &Shift) {
+        for (count, &asleep) in self.minutes.iter_mut().zip(shift.asleep.iter()) {
+            if asleep {
+                *count += 1;
+            }
         }
     }
 
@@ -136,35 +504,195 @@ impl SleepDistribution {
     }
 }
 
-#[derive(Debug, FromStr)]
-enum Entry {
-    #[adhoc(regex = r"^Guard #(?P<0>\d+) begins shift$")]
-    ShiftBegin(usize),
-    #[adhoc(regex = r"^falls asleep$")]
-    FallAsleep,
-    #[adhoc(regex = r"^wakes up$")]
-    WakeUp,
-}
+/// Generates the (unsorted) records of `size` shifts, starting on 1518-01-01.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    const FORMAT: &str = "%Y-%m-%d %H:%M";
+
+    let guards: Vec<usize> = (0..(size / 10).max(2))
+        .map(|_| rng.range_usize(10, 4000))
+        .collect();
+    let first_day = NaiveDate::from_ymd_opt(1518, 1, 1).unwrap();
+
+    let mut lines = Vec::new();
+    for day in 0..size {
+        let date = first_day + Duration::days(day as i64);
+        let shift_begin = if rng.chance(0.5) {
+            (date - Duration::days(1))
+                .and_hms_opt(23, rng.range_usize(45, 60) as u32, 0)
+                .unwrap()
+        } else {
+            date.and_hms_opt(0, rng.range_usize(0, 5) as u32, 0)
+                .unwrap()
+        };
+        lines.push(format!(
+            "[{}] Guard #{} begins shift",
+            shift_begin.format(FORMAT),
+            rng.choose(&guards).unwrap()
+        ));
+
+        let mut minute = 5;
+        while minute < 55 && rng.chance(0.7) {
+            let asleep = rng.range_usize(minute, 55);
+            let awake = rng.range_usize(asleep + 1, 60);
+            for (m, entry) in &[(asleep, "falls asleep"), (awake, "wakes up")] {
+                let timestamp = date.and_hms_opt(0, *m as u32, 0).unwrap();
+                lines.push(format!("[{}] {}", timestamp.format(FORMAT), entry));
+            }
+            minute = awake + 1;
+        }
+    }
 
-#[derive(Debug, FromStr)]
-#[adhoc(regex = r"^\[(?P<timestamp>.+)\] (?P<entry>.+)$")]
-struct Record {
-    #[adhoc(
-        construct_with = r#"NaiveDateTime::parse_from_str(timestamp: &str, "%Y-%m-%d %H:%M")?"#
-    )]
-    timestamp: NaiveDateTime,
-    entry: Entry,
+    rng.shuffle(&mut lines);
+    lines.iter().map(|line| format!("{}\n", line)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use util::error::ErrorKind;
+
+    const EXAMPLE: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up
+";
+
+    fn example_shifts() -> Vec<Shift> {
+        let records: Vec<Record> = EXAMPLE.lines().map(|l| l.parse().unwrap()).collect();
+        let (shifts, issues) = create_shifts(&records);
+        assert!(issues.is_empty());
+        shifts
+    }
+
+    #[test]
+    fn test_log_issues() {
+        let log = "\
+[1518-10-31 00:10] falls asleep
+[1518-10-31 23:58] Guard #10 begins shift
+[1518-10-31 23:59] falls asleep
+[1518-11-01 00:05] wakes up
+[1518-11-01 00:07] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:31] falls asleep
+[1518-11-01 01:10] wakes up
+[1518-11-02 00:00] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-03 00:20] wakes up
+[1518-11-04 00:01] Guard #10 begins shift
+[1518-11-04 00:50] falls asleep
+";
+        let records: Vec<Record> = log.lines().map(|l| l.parse().unwrap()).collect();
+        let (shifts, issues) = create_shifts(&records);
+
+        let kinds: Vec<(usize, IssueKind)> = issues
+            .iter()
+            .map(|issue| (issue.record, issue.kind))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, IssueKind::NoGuard),
+                (2, IssueKind::OutsideMidnightHour),
+                (4, IssueKind::NotAsleep),
+                (6, IssueKind::AlreadyAsleep),
+                (7, IssueKind::OutsideMidnightHour),
+                (9, IssueKind::NeverWakesUp),
+                (10, IssueKind::NoGuard),
+                (12, IssueKind::NeverWakesUp),
+            ],
+            kinds
+        );
+
+        assert_eq!(3, shifts.len());
+        let asleep =
+            |shift: &Shift| -> Vec<usize> { (0..MINUTES).filter(|&m| shift.asleep[m]).collect() };
+        assert_eq!((0..5).chain(30..60).collect::<Vec<_>>(), asleep(&shifts[0]));
+        assert_eq!((40..60).collect::<Vec<_>>(), asleep(&shifts[1]));
+        assert_eq!((50..60).collect::<Vec<_>>(), asleep(&shifts[2]));
+
+        assert!(validated_shifts(&records, false).is_err());
+        assert_eq!(3, validated_shifts(&records, true).unwrap().len());
+
+        let output = Output::new(4, Format::Json, false);
+        let records: Vec<Record> = log.lines().take(1).map(|l| l.parse().unwrap()).collect();
+        let shifts = validated_shifts(&records, true).unwrap();
+        assert_eq!(
+            ErrorKind::Parse,
+            print_answers(&shifts, &output).unwrap_err().kind()
+        );
+        assert!(print_answers(&[], &output).is_err());
+    }
+
+    #[test]
+    fn test_nights_table() {
+        assert_eq!(
+            "\
+Date   ID      Minute
+               000000000011111111112222222222333333333344444444445555555555
+               012345678901234567890123456789012345678901234567890123456789
+11-01  #10     .....####################.....#########################.....
+11-02  #99     ........................................##########..........
+11-03  #10     ........................#####...............................
+11-04  #99     ....................................##########..............
+11-05  #99     .............................................##########.....
+",
+            nights_table(&example_shifts())
+        );
+    }
+
+    #[test]
+    fn test_guards_report() {
+        let table = guards_table(&example_shifts());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(5, lines.len());
+        assert_eq!(
+            "#10          2      50    24  .....111111111111111111121111.1111111111111111111111111.....",
+            lines[3]
+        );
+        assert_eq!(
+            "#99          3      30    45  ....................................1111222223222211111.....",
+            lines[4]
+        );
+
+        let csv = guards_csv(&example_shifts());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("guard,shifts,minutes_asleep,minute_most_asleep,00,01,"));
+        assert!(lines[0].ends_with(",59"));
+        assert!(lines[1].starts_with("10,2,50,24,0,0,0,0,0,1,"));
+        assert_eq!(4 + MINUTES, lines[2].split(',').count());
+    }
+
+    #[test]
+    fn test_nights_csv() {
+        let csv = nights_csv(&example_shifts());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(6, lines.len());
+        assert!(lines[2].starts_with("1518-11-02,99,10,0,"));
+        let asleep: Vec<&str> = lines[2].split(',').skip(3).collect();
+        assert_eq!(MINUTES, asleep.len());
+        assert!(asleep[40..50].iter().all(|&a| a == "1"));
+        assert_eq!(10, asleep.iter().filter(|&&a| a == "1").count());
+    }
 
     #[test]
     fn test_part1() {
         let mut records: Vec<Record> = FileReader::new().read_from_file("input.txt").unwrap();
         records.sort_unstable_by_key(|r| r.timestamp);
-        let sleep_distributions = create_distributions(&records);
+        let sleep_distributions = create_distributions(&validated_shifts(&records, false).unwrap());
         let (guard_most_asleep, _) = find_guard_most_minutes_asleep(&sleep_distributions);
         let minute_asleep_most = sleep_distributions[&guard_most_asleep].minute_most_asleep();
         assert_eq!(36898, guard_most_asleep * minute_asleep_most);
@@ -174,9 +702,35 @@ mod tests {
     fn test_part2() {
         let mut records: Vec<Record> = FileReader::new().read_from_file("input.txt").unwrap();
         records.sort_unstable_by_key(|r| r.timestamp);
-        let sleep_distributions = create_distributions(&records);
+        let sleep_distributions = create_distributions(&validated_shifts(&records, false).unwrap());
         let (guard_most_asleep_at_same_minute, minute_asleep_most) =
             find_guard_most_asleep_at_same_minute(&sleep_distributions);
         assert_eq!(80711, guard_most_asleep_at_same_minute * minute_asleep_most);
     }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(&mut Rng::new(1), 30);
+        let mut records: Vec<Record> = input.lines().map(|l| l.parse().unwrap()).collect();
+        records.sort_unstable_by_key(|r| r.timestamp);
+        assert_eq!(
+            30,
+            records
+                .iter()
+                .filter(|r| matches!(r.entry, Entry::ShiftBegin(_)))
+                .count()
+        );
+        let (shifts, issues) = create_shifts(&records);
+        assert_eq!(30, shifts.len());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(4, solve, generate, size, seed).unwrap();
+            }
+        }
+    }
 }
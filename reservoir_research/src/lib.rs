@@ -0,0 +1,175 @@
+//! Scan line parser for day 17, shared by the solver and the fuzz targets.
+
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Line of clay reported by the ground scan.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScanLine {
+    VerticalLine(VerticalLine),
+    HorizontalLine(HorizontalLine),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VerticalLine {
+    pub x: usize,
+    pub y1: usize,
+    pub y2: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HorizontalLine {
+    pub y: usize,
+    pub x1: usize,
+    pub x2: usize,
+}
+
+impl std::fmt::Display for ScanLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanLine::VerticalLine(line) => write!(f, "x={}, y={}..{}", line.x, line.y1, line.y2),
+            ScanLine::HorizontalLine(line) => {
+                write!(f, "y={}, x={}..{}", line.y, line.x1, line.x2)
+            }
+        }
+    }
+}
+
+impl FromStr for ScanLine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('x') {
+            lazy_static! {
+                static ref REGEX: Regex = Regex::new(r"^x=(\d+), y=(\d+)\.\.(\d+)$").unwrap();
+            }
+            let captures = match REGEX.captures(s) {
+                Some(captures) => captures,
+                None => {
+                    return Err("input does not match expected format".to_string());
+                }
+            };
+            let mut values = [0; 3];
+            for (i, val) in values.iter_mut().enumerate() {
+                *val = match captures.get(i + 1) {
+                    Some(capture) => capture
+                        .as_str()
+                        .parse::<usize>()
+                        .map_err(|e| format!("cannot parse number: {}", e))?,
+                    None => {
+                        return Err("input does not match expected format".to_string());
+                    }
+                };
+            }
+            if captures.get(4).is_some() {
+                return Err("input does not match expected format".to_string());
+            }
+            if values[1] > values[2] {
+                return Err(format!("range {}..{} is reversed", values[1], values[2]));
+            }
+            Ok(ScanLine::VerticalLine(VerticalLine {
+                x: values[0],
+                y1: values[1],
+                y2: values[2],
+            }))
+        } else if s.starts_with('y') {
+            lazy_static! {
+                static ref REGEX: Regex = Regex::new(r"^y=(\d+), x=(\d+)\.\.(\d+)$").unwrap();
+            }
+            let captures = match REGEX.captures(s) {
+                Some(captures) => captures,
+                None => {
+                    return Err("input does not match expected format".to_string());
+                }
+            };
+            let mut values = [0; 3];
+            for (i, val) in values.iter_mut().enumerate() {
+                *val = match captures.get(i + 1) {
+                    Some(capture) => capture
+                        .as_str()
+                        .parse::<usize>()
+                        .map_err(|e| format!("cannot parse number: {}", e))?,
+                    None => {
+                        return Err("input does not match expected format".to_string());
+                    }
+                };
+            }
+            if captures.get(4).is_some() {
+                return Err("input does not match expected format".to_string());
+            }
+            if values[1] > values[2] {
+                return Err(format!("range {}..{} is reversed", values[1], values[2]));
+            }
+            Ok(ScanLine::HorizontalLine(HorizontalLine {
+                y: values[0],
+                x1: values[1],
+                x2: values[2],
+            }))
+        } else {
+            Err("input does not match expected format".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            ScanLine::VerticalLine(VerticalLine {
+                x: 495,
+                y1: 2,
+                y2: 7,
+            }),
+            "x=495, y=2..7".parse().unwrap()
+        );
+        assert_eq!(
+            ScanLine::HorizontalLine(HorizontalLine {
+                y: 7,
+                x1: 495,
+                x2: 501,
+            }),
+            "y=7, x=495..501".parse().unwrap()
+        );
+
+        for s in &[
+            "",
+            "x=495, y=2",
+            "x=495, x=2..7",
+            "x=495, y=7..2",
+            "y=-7, x=495..501",
+            "y=7, x=495..99999999999999999999",
+        ] {
+            assert!(s.parse::<ScanLine>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(17);
+        for _ in 0..1000 {
+            let position = rng.next_u64() as usize;
+            let start = rng.next_u64() as usize;
+            let end = start + rng.range_usize(0, usize::MAX - start);
+            let scan_line = if rng.chance(0.5) {
+                ScanLine::VerticalLine(VerticalLine {
+                    x: position,
+                    y1: start,
+                    y2: end,
+                })
+            } else {
+                ScanLine::HorizontalLine(HorizontalLine {
+                    y: position,
+                    x1: start,
+                    x2: end,
+                })
+            };
+            assert_eq!(scan_line, scan_line.to_string().parse().unwrap());
+        }
+    }
+}
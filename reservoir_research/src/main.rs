@@ -1,40 +1,40 @@
 use std::collections::VecDeque;
-use std::env;
 use std::io::BufRead;
-use std::str::FromStr;
 use std::{thread, time};
 
-use regex::Regex;
-
-use lazy_static::lazy_static;
-
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
 
+use reservoir_research::ScanLine;
+
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(17, solve);
+}
 
-    let input: Vec<ScanLine> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<ScanLine> = FileReader::new().read_from_file(input_file)?;
 
     let mut grid = Grid::create(&input);
+    output.diagnostic(format!(
+        "Dimensions: x = {}..{}, y= {}..{}",
+        grid.offset_x + 1,
+        grid.offset_x + grid.width - 2,
+        grid.lowest_y,
+        grid.height - 1
+    ));
     grid.fill_with_water();
 
-    println!("Filled with water: {}", grid.count_water());
-    println!(
-        "Retained after spring stops: {}",
-        grid.count_only_water_retained()
+    let water = grid.count_water();
+    output.answer(1, water, format!("Filled with water: {}", water));
+    let water_retained = grid.count_only_water_retained();
+    output.answer(
+        2,
+        water_retained,
+        format!("Retained after spring stops: {}", water_retained),
     );
+
+    Ok(())
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -342,10 +342,6 @@ impl Grid {
                 }
             }
         }
-        println!(
-            "Dimensions: x = {}..{}, y= {}..{}",
-            x_min, x_max, y_min, y_max
-        );
         (x_min, x_max, y_min, y_max)
     }
 
@@ -390,93 +386,3 @@ impl std::fmt::Display for Grid {
         Ok(())
     }
 }
-
-#[derive(Debug, Copy, Clone)]
-enum ScanLine {
-    VerticalLine(VerticalLine),
-    HorizontalLine(HorizontalLine),
-}
-
-#[derive(Debug, Copy, Clone)]
-struct VerticalLine {
-    x: usize,
-    y1: usize,
-    y2: usize,
-}
-
-#[derive(Debug, Copy, Clone)]
-struct HorizontalLine {
-    y: usize,
-    x1: usize,
-    x2: usize,
-}
-
-impl FromStr for ScanLine {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.starts_with('x') {
-            lazy_static! {
-                static ref REGEX: Regex = Regex::new(r"^x=(\d+), y=(\d+)\.\.(\d+)$").unwrap();
-            }
-            let captures = match REGEX.captures(s) {
-                Some(captures) => captures,
-                None => {
-                    return Err("input does not match expected format".to_string());
-                }
-            };
-            let mut values = [0; 3];
-            for (i, val) in values.iter_mut().enumerate() {
-                *val = match captures.get(i + 1) {
-                    Some(capture) => capture
-                        .as_str()
-                        .parse::<usize>()
-                        .map_err(|e| format!("cannot parse number: {}", e))?,
-                    None => {
-                        return Err("input does not match expected format".to_string());
-                    }
-                };
-            }
-            if captures.get(4).is_some() {
-                return Err("input does not match expected format".to_string());
-            }
-            Ok(ScanLine::VerticalLine(VerticalLine {
-                x: values[0],
-                y1: values[1],
-                y2: values[2],
-            }))
-        } else if s.starts_with('y') {
-            lazy_static! {
-                static ref REGEX: Regex = Regex::new(r"^y=(\d+), x=(\d+)\.\.(\d+)$").unwrap();
-            }
-            let captures = match REGEX.captures(s) {
-                Some(captures) => captures,
-                None => {
-                    return Err("input does not match expected format".to_string());
-                }
-            };
-            let mut values = [0; 3];
-            for (i, val) in values.iter_mut().enumerate() {
-                *val = match captures.get(i + 1) {
-                    Some(capture) => capture
-                        .as_str()
-                        .parse::<usize>()
-                        .map_err(|e| format!("cannot parse number: {}", e))?,
-                    None => {
-                        return Err("input does not match expected format".to_string());
-                    }
-                };
-            }
-            if captures.get(4).is_some() {
-                return Err("input does not match expected format".to_string());
-            }
-            Ok(ScanLine::HorizontalLine(HorizontalLine {
-                y: values[0],
-                x1: values[1],
-                x2: values[2],
-            }))
-        } else {
-            Err("input does not match expected format".to_string())
-        }
-    }
-}
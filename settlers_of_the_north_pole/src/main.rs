@@ -1,42 +1,37 @@
 use std::collections::HashSet;
-use std::env;
 
+use util::cli::{self, Output};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(18, solve);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
-    let mut grid = Grid::create(&input);
-    println!("{}", grid);
+    let mut grid = Grid::create(&input).context(ErrorKind::Parse, "Error reading map")?;
+    output.diagnostic(&grid);
 
     let num_steps = 10;
     for _ in 0..num_steps {
         grid.step();
     }
 
-    println!("After {} minutes:", num_steps);
-    println!("{}", grid);
+    output.diagnostic(format!("After {} minutes:", num_steps));
+    output.diagnostic(&grid);
 
     let counts = grid.count_all();
-    println!(
-        "Trees: {}, lumberyards: {} => {}",
-        counts.trees,
-        counts.lumberyard,
-        counts.trees * counts.lumberyard
+    output.answer(
+        1,
+        counts.trees * counts.lumberyard,
+        format!(
+            "Trees: {}, lumberyards: {} => {}",
+            counts.trees,
+            counts.lumberyard,
+            counts.trees * counts.lumberyard
+        ),
     );
 
     let mut set = HashSet::new();
@@ -58,12 +53,12 @@ fn main() {
 
         if !set.insert(counts) {
             if i - already_seen == 1 {
-                println!(
+                output.diagnostic(format!(
                     "[{}] {:?} => {}",
                     i,
                     counts,
                     counts.trees * counts.lumberyard
-                );
+                ));
                 if !loop_start_found {
                     loop_start_found = true;
                     loop_start = counts;
@@ -80,13 +75,19 @@ fn main() {
         }
     }
 
-    println!("Loop found: Length: {}", loop_entries.len());
+    output.diagnostic(format!("Loop found: Length: {}", loop_entries.len()));
     let resource_value = loop_entries[(num_steps - 1 - loop_start_idx) % loop_entries.len()];
-    println!(
-        "Resource value after {} minutes: {}",
-        num_steps,
-        resource_value.trees * resource_value.lumberyard
+    output.answer(
+        2,
+        resource_value.trees * resource_value.lumberyard,
+        format!(
+            "Resource value after {} minutes: {}",
+            num_steps,
+            resource_value.trees * resource_value.lumberyard
+        ),
     );
+
+    Ok(())
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -144,7 +145,7 @@ struct Grid {
 }
 
 impl Grid {
-    fn create(input: &[String]) -> Self {
+    fn create(input: &[String]) -> Result<Self, String> {
         let (width, height) = if !input.is_empty() {
             (input[0].len(), input.len())
         } else {
@@ -152,21 +153,21 @@ impl Grid {
         };
 
         let mut grid = Vec::with_capacity(width * height);
-        input.iter().flat_map(|s| s.chars()).for_each(|c| {
+        for c in input.iter().flat_map(|s| s.chars()) {
             let cell = match c {
                 '.' => Cell::Open,
                 '|' => Cell::Trees,
                 '#' => Cell::Lumberyard,
-                _ => panic!("unexpected input!"),
+                c => return Err(format!("unexpected input: {}", c)),
             };
             grid.push(cell);
-        });
+        }
 
-        Self {
+        Ok(Self {
             width,
             height,
             grid,
-        }
+        })
     }
 
     fn step(&mut self) {
@@ -0,0 +1,119 @@
+//! Point parser for day 10, shared by the solver and the fuzz targets.
+
+use std::str::FromStr;
+
+/// Light point with position and velocity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub x_position: i32,
+    pub y_position: i32,
+    pub x_velocity: i32,
+    pub y_velocity: i32,
+}
+
+impl Point {
+    /// Moves the point by its velocity.
+    pub fn propagate(&mut self) {
+        self.x_position = self.x_position.saturating_add(self.x_velocity);
+        self.y_position = self.y_position.saturating_add(self.y_velocity);
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "position=<{}, {}> velocity=<{}, {}>",
+            self.x_position, self.y_position, self.x_velocity, self.y_velocity
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum PointParseError {
+    ParseIntError(std::num::ParseIntError),
+    ParseError(String),
+}
+
+impl std::fmt::Display for PointParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointParseError::ParseIntError(e) => write!(f, "Error parsing int: {}", e),
+            PointParseError::ParseError(s) => write!(f, "Error parsing point: {}", s),
+        }
+    }
+}
+
+impl From<std::num::ParseIntError> for PointParseError {
+    fn from(error: std::num::ParseIntError) -> Self {
+        PointParseError::ParseIntError(error)
+    }
+}
+
+impl FromStr for Point {
+    type Err = PointParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let substrings: Vec<_> = s
+            .split(['<', '>', ','])
+            .map(|s| s.trim())
+            .filter(|&s| !s.is_empty())
+            .filter(|&s| !s.starts_with("position") && !s.starts_with("velocity"))
+            .collect();
+        if substrings.len() != 4 {
+            return Err(PointParseError::ParseError(String::from(
+                "input does not match format",
+            )));
+        }
+        Ok(Self {
+            x_position: substrings[0].parse()?,
+            y_position: substrings[1].parse()?,
+            x_velocity: substrings[2].parse()?,
+            y_velocity: substrings[3].parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Point {
+                x_position: 9,
+                y_position: 1,
+                x_velocity: 0,
+                y_velocity: -2,
+            },
+            "position=< 9,  1> velocity=< 0, -2>".parse().unwrap()
+        );
+
+        for s in &[
+            "",
+            "position=<9, 1> velocity=<0>",
+            "position=<9, 1> velocity=<0, 2, 3>",
+            "position=<a, 1> velocity=<0, 2>",
+            "position=<99999999999, 1> velocity=<0, 2>",
+        ] {
+            assert!(s.parse::<Point>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(10);
+        for _ in 0..1000 {
+            let mut value = || rng.range(i32::MIN.into(), i32::MAX.into()) as i32;
+            let point = Point {
+                x_position: value(),
+                y_position: value(),
+                x_velocity: value(),
+                y_velocity: value(),
+            };
+            assert_eq!(point, point.to_string().parse().unwrap());
+        }
+    }
+}
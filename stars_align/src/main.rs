@@ -1,44 +1,65 @@
-use std::env;
 use std::io::BufRead;
-use std::str::FromStr;
 
+use util::cli::{self, Format, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
 
+use stars_align::Point;
+
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(10, solve);
+}
 
-    let mut input: Vec<Point> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let mut input: Vec<Point> = FileReader::new().read_from_file(input_file)?;
 
-    for point in input.iter() {
-        println!("{:?}", point);
+    if output.diagnostics_enabled() {
+        for point in input.iter() {
+            output.diagnostic(format!("{:?}", point));
+        }
     }
 
+    // Candidate with the smallest bounding box: (area, seconds, rendered message)
+    let mut best_candidate: Option<(i64, usize, String)> = None;
+
     for i in 1..30000 {
         for point in input.iter_mut() {
             point.propagate();
         }
         let dims = determine_dimensions(&input);
         if dims.x_min >= -250 && dims.x_max <= 250 && dims.y_min >= -300 && dims.y_max <= 300 {
-            display_points(&input, dims);
-            println!("Seconds passed: {}", i);
-            let mut input_buffer = String::new();
-            let _ = std::io::stdin().lock().read_line(&mut input_buffer);
+            let area =
+                i64::from(dims.x_max - dims.x_min + 1) * i64::from(dims.y_max - dims.y_min + 1);
+            let message = render_points(&input, dims);
+            output.diagnostic(&message);
+            output.diagnostic(format!("Seconds passed: {}", i));
+            if output.format() == Format::Text {
+                let mut input_buffer = String::new();
+                let _ = std::io::stdin().lock().read_line(&mut input_buffer);
+            }
+
+            let is_best = match best_candidate {
+                Some((best_area, _, _)) => area < best_area,
+                None => true,
+            };
+            if is_best {
+                best_candidate = Some((area, i, message));
+            }
         }
     }
 
-    println!("Dimensions: {:?}", determine_dimensions(&input));
+    output.diagnostic(format!("Dimensions: {:?}", determine_dimensions(&input)));
+
+    if let Some((_, seconds, message)) = best_candidate {
+        output.answer(1, &message, format!("Message:\n{}", message));
+        output.answer(
+            2,
+            seconds,
+            format!("Seconds until message appears: {}", seconds),
+        );
+    }
+
+    Ok(())
 }
 
 fn determine_dimensions(points: &[Point]) -> Dimensions {
@@ -70,7 +91,7 @@ fn determine_dimensions(points: &[Point]) -> Dimensions {
     }
 }
 
-fn display_points(points: &[Point], dimensions: Dimensions) {
+fn render_points(points: &[Point], dimensions: Dimensions) -> String {
     let width = (dimensions.x_max - dimensions.x_min) as usize + 1;
     let height = (dimensions.y_max - dimensions.y_min) as usize + 1;
     let mut buffer = vec![b'.'; width * height];
@@ -79,12 +100,14 @@ fn display_points(points: &[Point], dimensions: Dimensions) {
             + (point.x_position - dimensions.x_min) as usize] = b'#';
     }
 
+    let mut rendered = String::with_capacity(buffer.len() + height);
     for (i, &b) in buffer.iter().enumerate() {
-        print!("{}", b as char);
-        if (i + 1) % width == 0 {
-            println!();
+        rendered.push(b as char);
+        if (i + 1) % width == 0 && i + 1 < buffer.len() {
+            rendered.push('\n');
         }
     }
+    rendered
 }
 
 #[derive(Debug)]
@@ -94,63 +117,3 @@ struct Dimensions {
     y_min: i32,
     y_max: i32,
 }
-
-#[derive(Debug)]
-struct Point {
-    x_position: i32,
-    y_position: i32,
-    x_velocity: i32,
-    y_velocity: i32,
-}
-
-impl Point {
-    fn propagate(&mut self) {
-        self.x_position = self.x_position.saturating_add(self.x_velocity);
-        self.y_position = self.y_position.saturating_add(self.y_velocity);
-    }
-}
-
-#[derive(Debug)]
-enum PointParseError {
-    ParseIntError(std::num::ParseIntError),
-    ParseError(String),
-}
-
-impl std::fmt::Display for PointParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PointParseError::ParseIntError(e) => write!(f, "Error parsing int: {}", e),
-            PointParseError::ParseError(s) => write!(f, "Error parsing point: {}", s),
-        }
-    }
-}
-
-impl From<std::num::ParseIntError> for PointParseError {
-    fn from(error: std::num::ParseIntError) -> Self {
-        PointParseError::ParseIntError(error)
-    }
-}
-
-impl FromStr for Point {
-    type Err = PointParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let substrings: Vec<_> = s
-            .split(|c| c == '<' || c == '>' || c == ',')
-            .map(|s| s.trim())
-            .filter(|&s| !s.is_empty())
-            .filter(|&s| !s.starts_with("position") && !s.starts_with("velocity"))
-            .collect();
-        if substrings.len() != 4 {
-            return Err(PointParseError::ParseError(String::from(
-                "input does not match format",
-            )));
-        }
-        Ok(Self {
-            x_position: substrings[0].parse()?,
-            y_position: substrings[1].parse()?,
-            x_velocity: substrings[2].parse()?,
-            y_velocity: substrings[3].parse()?,
-        })
-    }
-}
@@ -1,102 +1,53 @@
-use std::env;
-
+use util::cli::{self, Output};
+use util::error::Error;
 use util::input::{FileReader, FromFile};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run(12, solve);
+}
 
-    let input: Vec<String> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<String> = FileReader::new().read_from_file(input_file)?;
 
     let mut offset = -5;
     let mut initial_state: Vec<usize> = vec![0; -offset as usize];
     let mut patterns = [0; 32];
     for line in input.iter().filter(|&s| !s.is_empty()) {
         if line.starts_with("initial state") {
-            line.split(':')
+            for s in line
+                .split(':')
                 .map(|s| s.trim())
                 .filter(|&s| !s.is_empty())
                 .filter(|&s| s.starts_with(|c| c == '.' || c == '#'))
-                .flat_map(|s| s.chars())
-                .map(|c| {
-                    if c == '.' {
-                        0
-                    } else if c == '#' {
-                        1
-                    } else {
-                        println!("unexpected initial state!");
-                        std::process::exit(1);
-                    }
-                })
-                .for_each(|v| initial_state.push(v));
+            {
+                for c in s.chars() {
+                    initial_state.push(parse_pot(c)?);
+                }
+            }
         } else if line.starts_with(|c| c == '.' || c == '#') {
             let mut iter = line.split("=>").map(|s| s.trim()).filter(|s| !s.is_empty());
 
-            let mut p: usize;
+            let mut p: usize = 0;
             if let Some(pattern) = iter.next() {
-                p = 0;
-
                 if pattern.len() != 5 {
-                    println!("unexpected pattern length!");
-                    std::process::exit(1);
+                    return Err(Error::parse(format!("unexpected pattern length: {}", line)));
                 }
 
-                for (i, c) in pattern
-                    .chars()
-                    .map(|c| {
-                        if c == '.' {
-                            0
-                        } else if c == '#' {
-                            1
-                        } else {
-                            println!("unexpected initial state!");
-                            std::process::exit(1);
-                        }
-                    })
-                    .enumerate()
-                {
-                    p += (1 << (4 - i)) * c;
+                for (i, c) in pattern.chars().enumerate() {
+                    p += (1 << (4 - i)) * parse_pot(c)?;
                 }
             } else {
-                println!("Unexpected input!");
-                std::process::exit(1);
+                return Err(Error::parse(format!("Unexpected input: {}", line)));
             }
 
             if let Some(result) = iter.next() {
                 if result.len() != 1 {
-                    println!("unexpected result length!");
-                    std::process::exit(1);
+                    return Err(Error::parse(format!("unexpected result length: {}", line)));
                 }
 
-                let result = result
-                    .chars()
-                    .map(|c| {
-                        if c == '.' {
-                            0
-                        } else if c == '#' {
-                            1
-                        } else {
-                            println!("unexpected initial state!");
-                            std::process::exit(1);
-                        }
-                    })
-                    .nth(0)
-                    .unwrap();
-                patterns[p] = result;
+                patterns[p] = parse_pot(result.chars().next().unwrap())?;
             } else {
-                println!("Unexpected input!");
-                std::process::exit(1);
+                return Err(Error::parse(format!("Unexpected input: {}", line)));
             }
         }
     }
@@ -105,7 +56,7 @@ fn main() {
         initial_state.push(0);
     }
 
-    print_state(0, 0, &initial_state);
+    output.diagnostic(format_state(0, 0, &initial_state));
 
     let mut state = initial_state;
     let mut previous_sum = 0;
@@ -115,9 +66,9 @@ fn main() {
     for g in 1..=200 {
         let next_offset = calculate_next_gen(&mut state, offset, &patterns);
         offset = next_offset;
-        print_state(g, offset, &state);
+        output.diagnostic(format_state(g, offset, &state));
         let sum = calculate_sum_of_state(offset - 1, &state);
-        println!("sum: {} ({})", sum, sum - previous_sum);
+        output.diagnostic(format!("sum: {} ({})", sum, sum - previous_sum));
         previous_sum = sum;
         if g == 20 {
             sum_20 = sum;
@@ -128,11 +79,23 @@ fn main() {
         }
     }
 
-    println!("sum (20 generations): {}", sum_20);
-    println!(
-        "sum (very many generations): {}",
-        (50_000_000_000u64 - 200) * (sum_200 as u64 - sum_199 as u64) + sum_200 as u64
+    output.answer(1, sum_20, format!("sum (20 generations): {}", sum_20));
+    let sum_many = (50_000_000_000u64 - 200) * (sum_200 as u64 - sum_199 as u64) + sum_200 as u64;
+    output.answer(
+        2,
+        sum_many,
+        format!("sum (very many generations): {}", sum_many),
     );
+
+    Ok(())
+}
+
+fn parse_pot(c: char) -> Result<usize, Error> {
+    match c {
+        '.' => Ok(0),
+        '#' => Ok(1),
+        c => Err(Error::parse(format!("unexpected pot state: {}", c))),
+    }
 }
 
 fn calculate_next_gen(
@@ -180,16 +143,16 @@ fn calculate_sum_of_state(offset: isize, state: &[usize]) -> isize {
         .sum()
 }
 
-fn print_state(generation: usize, zero_position: isize, state: &[usize]) {
-    print!("{} ({}): ", generation, zero_position);
+fn format_state(generation: usize, zero_position: isize, state: &[usize]) -> String {
+    let mut formatted = format!("{} ({}): ", generation, zero_position);
     for elem in state {
         if *elem == 0 {
-            print!(".");
+            formatted.push('.');
         } else if *elem == 1 {
-            print!("#");
+            formatted.push('#');
         } else {
-            print!("?");
+            formatted.push('?');
         }
     }
-    println!();
+    formatted
 }
@@ -0,0 +1,885 @@
+//! Dependency parser and scheduler for day 7, shared by the solver and the fuzz targets.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::str::FromStr;
+
+/// Dependency between two steps: `before` must be finished before `step` can begin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub before: String,
+    pub step: String,
+}
+
+impl FromStr for Dependency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words.as_slice() {
+            ["Step", before, "must", "be", "finished", "before", "step", step, "can", "begin."] => {
+                Ok(Self {
+                    before: before.to_string(),
+                    step: step.to_string(),
+                })
+            }
+            _ => Err(String::from("input does not match format")),
+        }
+    }
+}
+
+impl std::fmt::Display for Dependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Step {} must be finished before step {} can begin.",
+            self.before, self.step
+        )
+    }
+}
+
+/// Duration of a step in seconds, overriding its default duration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepDuration {
+    pub step: String,
+    pub seconds: u64,
+}
+
+impl FromStr for StepDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words.as_slice() {
+            ["Step", step, "takes", seconds, "seconds."] => Ok(Self {
+                step: step.to_string(),
+                seconds: seconds
+                    .parse()
+                    .map_err(|e| format!("invalid duration \"{}\": {}", seconds, e))?,
+            }),
+            _ => Err(String::from("input does not match format")),
+        }
+    }
+}
+
+impl std::fmt::Display for StepDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Step {} takes {} seconds.", self.step, self.seconds)
+    }
+}
+
+/// Line of the input: either a dependency or the duration of a step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Dependency(Dependency),
+    Duration(StepDuration),
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.split_whitespace().nth(2) == Some("takes") {
+            s.parse().map(Instruction::Duration)
+        } else {
+            s.parse().map(Instruction::Dependency)
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Dependency(dependency) => dependency.fmt(f),
+            Instruction::Duration(duration) => duration.fmt(f),
+        }
+    }
+}
+
+/// Dependency graph of named steps.
+///
+/// Steps are numbered in the order in which they are first mentioned.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    dependencies: Vec<Vec<usize>>,
+    dependents: Vec<Vec<usize>>,
+    durations: Vec<Option<u64>>,
+    /// Whether the step was added as a step or by a dependency, not only by its duration.
+    declared: Vec<bool>,
+}
+
+impl Graph {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the graph described by `instructions`.
+    pub fn from_instructions(instructions: &[Instruction]) -> Self {
+        let mut graph = Self::new();
+        for instruction in instructions {
+            match instruction {
+                Instruction::Dependency(dependency) => {
+                    graph.add_dependency(&dependency.before, &dependency.step)
+                }
+                Instruction::Duration(duration) => {
+                    graph.set_duration(&duration.step, duration.seconds)
+                }
+            }
+        }
+        graph
+    }
+
+    /// Returns the number of steps.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns whether the graph has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns the index of step `name`, adding it if it doesn't exist yet.
+    pub fn add_step(&mut self, name: &str) -> usize {
+        let i = self.insert_step(name);
+        self.declared[i] = true;
+        i
+    }
+
+    /// Like `add_step`, but doesn't mark a new step as declared.
+    fn insert_step(&mut self, name: &str) -> usize {
+        if let Some(&i) = self.index.get(name) {
+            return i;
+        }
+        let i = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), i);
+        self.dependencies.push(Vec::new());
+        self.dependents.push(Vec::new());
+        self.durations.push(None);
+        self.declared.push(false);
+        i
+    }
+
+    /// Adds the dependency that `before` must be finished before `step` can begin, adding the
+    /// steps if necessary.
+    pub fn add_dependency(&mut self, before: &str, step: &str) {
+        let before = self.add_step(before);
+        let step = self.add_step(step);
+        if !self.dependencies[step].contains(&before) {
+            self.dependencies[step].push(before);
+            self.dependents[before].push(step);
+        }
+    }
+
+    /// Sets the duration of `step` in seconds, adding the step if necessary.
+    ///
+    /// A step that is only mentioned here, but neither added by `add_step` nor part of a
+    /// dependency, makes the graph invalid: it is most likely a misspelled step name.
+    pub fn set_duration(&mut self, step: &str, seconds: u64) {
+        let step = self.insert_step(step);
+        self.durations[step] = Some(seconds);
+    }
+
+    /// Returns the index of step `name`.
+    pub fn step(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Returns the name of step `i`.
+    pub fn name(&self, i: usize) -> &str {
+        &self.names[i]
+    }
+
+    /// Returns the steps that must be finished before step `i` can begin.
+    pub fn dependencies(&self, i: usize) -> &[usize] {
+        &self.dependencies[i]
+    }
+
+    /// Returns the steps that can't begin before step `i` is finished.
+    pub fn dependents(&self, i: usize) -> &[usize] {
+        &self.dependents[i]
+    }
+
+    /// Returns the duration of step `i`, if it was set.
+    pub fn duration(&self, i: usize) -> Option<u64> {
+        self.durations[i]
+    }
+
+    /// Checks that all steps are known and can be executed, i.e. that the graph has no
+    /// dependency cycle.
+    ///
+    /// # Failures
+    /// Returns an error naming the steps that only have a duration (see `set_duration`).
+    /// Otherwise, returns an error naming the steps of a cycle (starting with the alphabetically
+    /// first one), as well as all other steps that can't begin because they depend on a cycle.
+    pub fn validate(&self) -> Result<(), String> {
+        let unknown: Vec<&str> = (0..self.len())
+            .filter(|&i| !self.declared[i])
+            .map(|i| self.name(i))
+            .collect();
+        match unknown.as_slice() {
+            [] => self.topological_order().map(|_| ()),
+            [step] => Err(format!("duration given for unknown step {}", step)),
+            steps => Err(format!(
+                "duration given for unknown steps {}",
+                steps.join(", ")
+            )),
+        }
+    }
+
+    /// Returns the steps in an order in which every step comes after its dependencies.
+    ///
+    /// # Failures
+    /// Returns the same error as [`Graph::validate`] if the graph has a cycle.
+    pub fn topological_order(&self) -> Result<Vec<usize>, String> {
+        // Remove steps without unfinished dependencies until only the blocked ones remain
+        let mut missing_dependencies: Vec<usize> = (0..self.len())
+            .map(|i| self.dependencies(i).len())
+            .collect();
+        let mut ready: Vec<usize> = (0..self.len())
+            .filter(|&i| missing_dependencies[i] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.len());
+        let mut blocked = vec![true; self.len()];
+        while let Some(i) = ready.pop() {
+            order.push(i);
+            blocked[i] = false;
+            for &dependent in self.dependents(i) {
+                missing_dependencies[dependent] -= 1;
+                if missing_dependencies[dependent] == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+        let start = match (0..self.len()).find(|&i| blocked[i]) {
+            Some(start) => start,
+            None => return Ok(order),
+        };
+
+        // Every blocked step has a blocked dependency, so following them leads into a cycle
+        let mut visited = vec![false; self.len()];
+        let mut path = Vec::new();
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            path.push(i);
+            i = *self
+                .dependencies(i)
+                .iter()
+                .find(|&&dependency| blocked[dependency])
+                .unwrap();
+        }
+        let mut cycle = path.split_off(path.iter().position(|&j| j == i).unwrap());
+        cycle.reverse();
+        let first = (0..cycle.len())
+            .min_by_key(|&j| self.name(cycle[j]))
+            .unwrap();
+        cycle.rotate_left(first);
+        cycle.push(cycle[0]);
+
+        let mut message = format!(
+            "dependency cycle {}",
+            cycle
+                .iter()
+                .map(|&i| self.name(i))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        );
+        let mut others: Vec<&str> = (0..self.len())
+            .filter(|&i| blocked[i] && !cycle.contains(&i))
+            .map(|i| self.name(i))
+            .collect();
+        if !others.is_empty() {
+            others.sort_unstable();
+            message.push_str(&format!(" (also blocking {})", others.join(", ")));
+        }
+        Err(message)
+    }
+}
+
+/// Order in which steps that are ready at the same time are started.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Alphabetically first name first (the order of the puzzle).
+    Name,
+    /// Alphabetically last name first.
+    ReverseName,
+    /// First mentioned step first.
+    Input,
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(TieBreak::Name),
+            "reverse-name" => Ok(TieBreak::ReverseName),
+            "input" => Ok(TieBreak::Input),
+            s => Err(format!(
+                "Unknown tie break \"{}\" (expected \"name\", \"reverse-name\" or \"input\")",
+                s
+            )),
+        }
+    }
+}
+
+/// Step of a schedule, with the worker executing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledStep {
+    pub step: String,
+    pub worker: usize,
+    pub start: u64,
+    pub finish: u64,
+}
+
+/// Schedule of the steps of a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// Number of workers (numbered from 0).
+    pub workers: usize,
+    /// Scheduled steps, ordered by start time. Steps started at the same time are in tie break
+    /// order.
+    pub steps: Vec<ScheduledStep>,
+    /// Time at which the last step is finished.
+    pub finish_time: u64,
+}
+
+impl Schedule {
+    /// Returns the names of the steps in the order they are started.
+    pub fn order(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.step.as_str()).collect()
+    }
+
+    /// Returns the step worker `worker` is busy with at `time`, if any.
+    pub fn step_at(&self, worker: usize, time: u64) -> Option<&ScheduledStep> {
+        self.steps
+            .iter()
+            .find(|step| step.worker == worker && step.start <= time && time < step.finish)
+    }
+}
+
+/// Timing of a step without a limit on the number of workers, as computed by the critical path
+/// method.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StepTiming {
+    pub duration: u64,
+    /// Earliest time at which all dependencies can be finished.
+    pub earliest_start: u64,
+    /// Latest start time that doesn't delay the end of the project.
+    pub latest_start: u64,
+}
+
+impl StepTiming {
+    /// Returns by how much the step can be delayed without delaying the end of the project.
+    pub fn slack(&self) -> u64 {
+        self.latest_start - self.earliest_start
+    }
+
+    /// Returns whether the step is on a critical path, i.e. has no slack.
+    pub fn is_critical(&self) -> bool {
+        self.slack() == 0
+    }
+}
+
+/// Critical path analysis of a graph: the timing of every step if there are enough workers to
+/// start all steps as soon as they are ready.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalPath {
+    /// Timing of every step, by index.
+    pub timings: Vec<StepTiming>,
+    /// Minimum time to finish all steps, the length of the critical path.
+    pub length: u64,
+    /// Longest chain of dependent steps (with ties broken by index).
+    pub path: Vec<usize>,
+}
+
+/// Schedules the steps of a graph on a number of workers.
+///
+/// Whenever a worker is idle, it starts the ready step that comes first according to the tie
+/// break. Steps without a duration in the graph take the base duration, plus the position in
+/// the alphabet (`A` = 1) for steps named by a single letter.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    workers: usize,
+    base_duration: u64,
+    tie_break: TieBreak,
+}
+
+impl Scheduler {
+    /// Creates a scheduler with `workers` workers, a base duration of 0 and the puzzle's tie
+    /// break.
+    ///
+    /// # Panics
+    /// Panics if `workers` is 0.
+    pub fn new(workers: usize) -> Self {
+        assert!(workers > 0, "at least one worker is required");
+        Self {
+            workers,
+            base_duration: 0,
+            tie_break: TieBreak::Name,
+        }
+    }
+
+    pub fn base_duration(self, base_duration: u64) -> Self {
+        Self {
+            base_duration,
+            ..self
+        }
+    }
+
+    pub fn tie_break(self, tie_break: TieBreak) -> Self {
+        Self { tie_break, ..self }
+    }
+
+    /// Returns the duration of step `i` of `graph`, or an error if it doesn't fit in a `u64`.
+    pub fn duration(&self, graph: &Graph, i: usize) -> Result<u64, String> {
+        match (graph.duration(i), graph.name(i).as_bytes()) {
+            (Some(duration), _) => Ok(duration),
+            (None, [letter]) if letter.is_ascii_alphabetic() => self
+                .base_duration
+                .checked_add((letter.to_ascii_uppercase() - b'A') as u64 + 1)
+                .ok_or_else(too_long),
+            (None, _) => Ok(self.base_duration),
+        }
+    }
+
+    /// Computes the critical path of `graph`, using the durations of this scheduler but ignoring
+    /// its number of workers.
+    ///
+    /// # Failures
+    /// Returns an error if the graph isn't valid (see [`Graph::validate`]), or if a time doesn't
+    /// fit in a `u64`.
+    pub fn critical_path(&self, graph: &Graph) -> Result<CriticalPath, String> {
+        graph.validate()?;
+        let order = graph.topological_order()?;
+
+        let mut timings: Vec<StepTiming> = (0..graph.len())
+            .map(|i| {
+                Ok(StepTiming {
+                    duration: self.duration(graph, i)?,
+                    earliest_start: 0,
+                    latest_start: 0,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        let earliest_finish = |timing: &StepTiming| {
+            timing
+                .earliest_start
+                .checked_add(timing.duration)
+                .ok_or_else(too_long)
+        };
+        let latest = |latest: u64, finish: Result<u64, String>| finish.map(|f| latest.max(f));
+        for &i in order.iter() {
+            timings[i].earliest_start = graph
+                .dependencies(i)
+                .iter()
+                .map(|&dependency| earliest_finish(&timings[dependency]))
+                .try_fold(0, latest)?;
+        }
+        let length = timings.iter().map(earliest_finish).try_fold(0, latest)?;
+        for &i in order.iter().rev() {
+            let latest_finish = graph
+                .dependents(i)
+                .iter()
+                .map(|&dependent| timings[dependent].latest_start)
+                .min()
+                .unwrap_or(length);
+            timings[i].latest_start = latest_finish - timings[i].duration;
+        }
+
+        // Follow critical steps from a first to a last step
+        let mut path = Vec::new();
+        let mut next = (0..graph.len())
+            .find(|&i| timings[i].is_critical() && graph.dependencies(i).is_empty());
+        while let Some(i) = next {
+            path.push(i);
+            next = graph.dependents(i).iter().copied().find(|&dependent| {
+                timings[dependent].is_critical()
+                    && Ok(timings[dependent].earliest_start) == earliest_finish(&timings[i])
+            });
+        }
+
+        Ok(CriticalPath {
+            timings,
+            length,
+            path,
+        })
+    }
+
+    /// Schedules the steps of `graph`.
+    ///
+    /// Time jumps from one finishing step to the next, when its worker becomes idle and its
+    /// dependents may become ready.
+    ///
+    /// # Failures
+    /// Returns an error if the graph isn't valid (see [`Graph::validate`]), or if a time doesn't
+    /// fit in a `u64`.
+    pub fn schedule(&self, graph: &Graph) -> Result<Schedule, String> {
+        graph.validate()?;
+
+        let priority = self.priorities(graph);
+        let mut missing_dependencies: Vec<usize> = (0..graph.len())
+            .map(|i| graph.dependencies(i).len())
+            .collect();
+
+        let mut ready: BinaryHeap<Reverse<(usize, usize)>> = (0..graph.len())
+            .filter(|&i| missing_dependencies[i] == 0)
+            .map(|i| Reverse((priority[i], i)))
+            .collect();
+        let mut idle: BinaryHeap<Reverse<usize>> = (0..self.workers).map(Reverse).collect();
+        let mut running: BinaryHeap<Reverse<(u64, usize, usize)>> = BinaryHeap::new();
+
+        let mut steps = Vec::with_capacity(graph.len());
+        let mut time: u64 = 0;
+        loop {
+            while !ready.is_empty() && !idle.is_empty() {
+                let Reverse((_, i)) = ready.pop().unwrap();
+                let Reverse(worker) = idle.pop().unwrap();
+                let finish = time
+                    .checked_add(self.duration(graph, i)?)
+                    .ok_or_else(too_long)?;
+                running.push(Reverse((finish, worker, i)));
+                steps.push(ScheduledStep {
+                    step: graph.name(i).to_string(),
+                    worker,
+                    start: time,
+                    finish,
+                });
+            }
+
+            // Finish all steps ending at the next finish time
+            time = match running.peek() {
+                Some(Reverse((finish, _, _))) => *finish,
+                None => break,
+            };
+            while let Some(&Reverse((finish, worker, i))) = running.peek() {
+                if finish != time {
+                    break;
+                }
+                running.pop();
+                idle.push(Reverse(worker));
+                for &dependent in graph.dependents(i) {
+                    missing_dependencies[dependent] -= 1;
+                    if missing_dependencies[dependent] == 0 {
+                        ready.push(Reverse((priority[dependent], dependent)));
+                    }
+                }
+            }
+        }
+
+        Ok(Schedule {
+            workers: self.workers,
+            finish_time: steps.iter().map(|step| step.finish).max().unwrap_or(0),
+            steps,
+        })
+    }
+
+    /// Returns the rank of every step in the tie break order.
+    fn priorities(&self, graph: &Graph) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..graph.len()).collect();
+        match self.tie_break {
+            TieBreak::Name => order.sort_by(|&a, &b| graph.name(a).cmp(graph.name(b))),
+            TieBreak::ReverseName => order.sort_by(|&a, &b| graph.name(b).cmp(graph.name(a))),
+            TieBreak::Input => {}
+        }
+
+        let mut priority = vec![0; graph.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            priority[i] = rank;
+        }
+        priority
+    }
+}
+
+/// Error for times that don't fit in a `u64`.
+fn too_long() -> String {
+    format!("steps take longer than {} seconds", u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::random::Rng;
+
+    const EXAMPLE: &str = "Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.";
+
+    fn example() -> Graph {
+        let instructions: Vec<Instruction> = EXAMPLE.lines().map(|l| l.parse().unwrap()).collect();
+        Graph::from_instructions(&instructions)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Dependency {
+                before: String::from("C"),
+                step: String::from("A"),
+            },
+            "Step C must be finished before step A can begin."
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            Instruction::Dependency(Dependency {
+                before: String::from("build-1"),
+                step: String::from("test"),
+            }),
+            "Step build-1 must be finished before step test can begin."
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            Instruction::Duration(StepDuration {
+                step: String::from("test"),
+                seconds: 12,
+            }),
+            "Step test takes 12 seconds.".parse().unwrap()
+        );
+
+        for s in &[
+            "",
+            "Step C must be finished before step A B can begin.",
+            "Step C must be finished before step A can begin. B",
+            "Step C must be finished after step A can begin.",
+            "Step A takes -1 seconds.",
+            "Step A takes 1 second.",
+        ] {
+            assert!(s.parse::<Instruction>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(7);
+        let names = ["A", "Z", "b", "step-1", "42", "ü"];
+        for _ in 0..1000 {
+            let instruction = if rng.chance(0.5) {
+                Instruction::Dependency(Dependency {
+                    before: rng.choose(&names).unwrap().to_string(),
+                    step: rng.choose(&names).unwrap().to_string(),
+                })
+            } else {
+                Instruction::Duration(StepDuration {
+                    step: rng.choose(&names).unwrap().to_string(),
+                    seconds: rng.next_u64(),
+                })
+            };
+            assert_eq!(instruction, instruction.to_string().parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_schedule_example() {
+        let graph = example();
+        let schedule = Scheduler::new(1).schedule(&graph).unwrap();
+        assert_eq!("CABDFE", schedule.order().concat());
+
+        let schedule = Scheduler::new(2).schedule(&graph).unwrap();
+        assert_eq!("CAFBDE", schedule.order().concat());
+        assert_eq!(15, schedule.finish_time);
+        assert_eq!(
+            ScheduledStep {
+                step: String::from("F"),
+                worker: 1,
+                start: 3,
+                finish: 9,
+            },
+            schedule.steps[2]
+        );
+
+        let schedule = Scheduler::new(2)
+            .tie_break(TieBreak::ReverseName)
+            .schedule(&graph)
+            .unwrap();
+        assert_eq!("CFADBE", schedule.order().concat());
+        let schedule = Scheduler::new(2)
+            .base_duration(60)
+            .tie_break(TieBreak::Input)
+            .schedule(&graph)
+            .unwrap();
+        assert_eq!("CAFBDE", schedule.order().concat());
+        assert_eq!(258, schedule.finish_time);
+    }
+
+    #[test]
+    fn test_schedule_durations() {
+        let mut graph = Graph::new();
+        graph.add_dependency("compile", "test");
+        graph.add_dependency("compile", "docs");
+        graph.add_dependency("test", "release");
+        graph.add_dependency("docs", "release");
+        graph.add_step("lint");
+        graph.set_duration("compile", 10);
+        graph.set_duration("test", 7);
+        graph.set_duration("docs", 3);
+
+        let scheduler = Scheduler::new(2)
+            .base_duration(1)
+            .tie_break(TieBreak::Input);
+        assert_eq!(
+            Ok(1),
+            scheduler.duration(&graph, graph.step("lint").unwrap())
+        );
+
+        let schedule = scheduler.schedule(&graph).unwrap();
+        let timing: Vec<_> = schedule
+            .steps
+            .iter()
+            .map(|step| (step.step.as_str(), step.worker, step.start, step.finish))
+            .collect();
+        assert_eq!(
+            vec![
+                ("compile", 0, 0, 10),
+                ("lint", 1, 0, 1),
+                ("test", 0, 10, 17),
+                ("docs", 1, 10, 13),
+                ("release", 0, 17, 18),
+            ],
+            timing
+        );
+        assert_eq!(18, schedule.finish_time);
+    }
+
+    #[test]
+    fn test_schedule_timeline() {
+        let schedule = Scheduler::new(2).schedule(&example()).unwrap();
+        assert_eq!(
+            Some("C"),
+            schedule.step_at(0, 2).map(|step| step.step.as_str())
+        );
+        assert_eq!(None, schedule.step_at(1, 2));
+        assert_eq!(
+            Some("F"),
+            schedule.step_at(1, 8).map(|step| step.step.as_str())
+        );
+        assert_eq!(None, schedule.step_at(1, 9));
+        assert_eq!(
+            Some("E"),
+            schedule.step_at(0, 14).map(|step| step.step.as_str())
+        );
+        assert_eq!(None, schedule.step_at(0, 15));
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let graph = example();
+        let critical_path = Scheduler::new(1).critical_path(&graph).unwrap();
+        assert_eq!(14, critical_path.length);
+        let path: Vec<&str> = critical_path.path.iter().map(|&i| graph.name(i)).collect();
+        assert_eq!(vec!["C", "F", "E"], path);
+
+        let slack = |name| critical_path.timings[graph.step(name).unwrap()].slack();
+        assert_eq!(
+            vec![0, 1, 0, 3, 1, 0],
+            ["C", "A", "F", "B", "D", "E"]
+                .iter()
+                .map(|&name| slack(name))
+                .collect::<Vec<_>>()
+        );
+        let a = critical_path.timings[graph.step("A").unwrap()];
+        assert_eq!((3, 4), (a.earliest_start, a.latest_start));
+
+        // Enough workers reach the length of the critical path
+        assert_eq!(14, Scheduler::new(3).schedule(&graph).unwrap().finish_time);
+
+        let mut graph = Graph::new();
+        graph.add_dependency("A", "B");
+        graph.add_dependency("B", "A");
+        assert!(Scheduler::new(1).critical_path(&graph).is_err());
+        let empty = Scheduler::new(1).critical_path(&Graph::new()).unwrap();
+        assert_eq!((0, Vec::new()), (empty.length, empty.path));
+    }
+
+    #[test]
+    fn test_schedule_cycle() {
+        let mut graph = Graph::new();
+        graph.add_dependency("A", "B");
+        graph.add_dependency("B", "C");
+        graph.add_dependency("C", "B");
+        graph.add_dependency("C", "D");
+        graph.add_dependency("D", "E");
+        assert_eq!(
+            Err(String::from(
+                "dependency cycle B -> C -> B (also blocking D, E)"
+            )),
+            Scheduler::new(1).schedule(&graph)
+        );
+
+        let mut graph = Graph::new();
+        for (before, step) in &[("x", "y"), ("z", "w"), ("y", "z"), ("w", "x"), ("a", "x")] {
+            graph.add_dependency(before, step);
+        }
+        assert_eq!(
+            Err(String::from("dependency cycle w -> x -> y -> z -> w")),
+            graph.validate()
+        );
+
+        let mut graph = Graph::new();
+        graph.add_dependency("A", "A");
+        assert_eq!(
+            Err(String::from("dependency cycle A -> A")),
+            graph.validate()
+        );
+
+        assert_eq!(Ok(()), example().validate());
+        assert_eq!(Ok(()), Graph::new().validate());
+    }
+
+    #[test]
+    fn test_unknown_steps() {
+        let instructions: Vec<Instruction> = [
+            "Step test takes 7 seconds.",
+            "Step compile must be finished before step test can begin.",
+            "Step tset takes 3 seconds.",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+        let mut graph = Graph::from_instructions(&instructions);
+        let error = Err(String::from("duration given for unknown step tset"));
+        assert_eq!(error, graph.validate());
+        assert_eq!(error, Scheduler::new(1).schedule(&graph).map(|_| ()));
+        assert_eq!(error, Scheduler::new(1).critical_path(&graph).map(|_| ()));
+
+        graph.set_duration("docs", 1);
+        assert_eq!(
+            Err(String::from("duration given for unknown steps tset, docs")),
+            graph.validate()
+        );
+
+        graph.add_step("tset");
+        graph.add_dependency("compile", "docs");
+        assert_eq!(Ok(()), graph.validate());
+    }
+
+    #[test]
+    fn test_overflowing_durations() {
+        let mut graph = Graph::new();
+        graph.set_duration("A", u64::MAX);
+        graph.add_step("A");
+        let scheduler = Scheduler::new(1);
+        assert_eq!(u64::MAX, scheduler.schedule(&graph).unwrap().finish_time);
+        assert_eq!(u64::MAX, scheduler.critical_path(&graph).unwrap().length);
+
+        graph.add_dependency("A", "B");
+        let error = Err(format!("steps take longer than {} seconds", u64::MAX));
+        assert_eq!(error, scheduler.schedule(&graph).map(|_| ()));
+        assert_eq!(error, scheduler.critical_path(&graph).map(|_| ()));
+
+        let scheduler = Scheduler::new(1).base_duration(u64::MAX);
+        let b = graph.step("B").unwrap();
+        assert_eq!(error, scheduler.duration(&graph, b).map(|_| ()));
+        assert_eq!(error, scheduler.schedule(&example()).map(|_| ()));
+    }
+}
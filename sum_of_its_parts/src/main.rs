@@ -1,368 +1,492 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::env;
-use std::str::FromStr;
+use std::collections::HashSet;
+use std::fmt::Write;
 
+use util::cli::{self, Output, SubcommandArgs};
+use util::error::{Context, Error, ErrorKind};
 use util::input::{FileReader, FromFile};
+use util::random::Rng;
 
-const DEBUG: bool = false;
+use sum_of_its_parts::{
+    CriticalPath, Dependency, Graph, Instruction, Schedule, ScheduledStep, Scheduler, StepDuration,
+    TieBreak,
+};
 
 fn main() {
-    let input_file = match env::args().nth(1) {
-        Some(input_file) => input_file,
-        None => {
-            println!("Please supply input file!");
-            std::process::exit(1);
-        }
-    };
+    cli::run_with_subcommands(
+        7,
+        solve,
+        Some(generate),
+        &[
+            ("schedule", schedule_command),
+            ("critical-path", critical_path_command),
+            ("dot", dot_command),
+        ],
+    );
+}
 
-    let input: Vec<Dependency> = match FileReader::new().read_from_file(input_file) {
-        Ok(input) => input,
-        Err(e) => {
-            println!("Error reading input: {}", e);
-            std::process::exit(1);
-        }
-    };
+fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+    let input: Vec<Instruction> = FileReader::new().read_from_file(input_file)?;
+    let graph = Graph::from_instructions(&input);
 
-    let (unprocessed_fwd, mut unprocessed_bwd) = create_dependencies(&input);
+    let order = Scheduler::new(1)
+        .schedule(&graph)
+        .context(ErrorKind::Parse, "Invalid dependency graph")?
+        .order()
+        .concat();
 
-    let order = part1(&unprocessed_fwd.clone(), &mut unprocessed_bwd.clone());
+    output.answer(1, &order, format!("Order (alone): {}\n", order));
 
-    println!("Order (alone): {}", order);
-    println!();
+    let schedule = Scheduler::new(5)
+        .base_duration(60)
+        .schedule(&graph)
+        .context(ErrorKind::Parse, "Invalid dependency graph")?;
 
-    let (order, finish_time) = part2(&unprocessed_fwd, &mut unprocessed_bwd);
+    output.diagnostic(format!("Order (with help): {}", schedule.order().concat()));
+    output.answer(
+        2,
+        schedule.finish_time,
+        format!("Finish Time: {}", schedule.finish_time),
+    );
 
-    println!("Order (with help): {}", order);
-    println!("Finish Time: {}", finish_time);
+    Ok(())
 }
 
-fn create_dependencies(dependencies: &[Dependency]) -> (Vec<Step>, Vec<Step>) {
-    let mut unprocessed_fwd = Vec::new();
-    let mut unprocessed_bwd = Vec::new();
-    for i in b'A'..=b'Z' {
-        unprocessed_fwd.push(Step::new(i));
-        unprocessed_bwd.push(Step::new(i));
-    }
-
-    for dep in dependencies.iter() {
-        unprocessed_fwd[StepId::idx(dep.before)]
-            .dependents
-            .push(dep.step);
-        unprocessed_bwd[StepId::idx(dep.step)]
-            .dependencies
-            .push(dep.before);
+/// Largest finish time for which `--gantt` prints a row per second.
+const MAX_GANTT_SECONDS: u64 = 100_000;
+
+/// Prints the schedule of the steps, with the worker, start and finish time of every step, or
+/// with `--gantt` what every worker does in every second (up to `MAX_GANTT_SECONDS`).
+///
+/// Usage: `schedule <input file> [--workers <n>] [--base-duration <seconds>]
+/// [--tie-break name|reverse-name|input] [--gantt]`. Defaults are those of the puzzle: 5
+/// workers, a base duration of 60 seconds and the alphabetical tie break. Besides the
+/// dependencies, the input may contain lines like `Step A takes 10 seconds.` to set the
+/// duration of a step.
+fn schedule_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &["gantt"], &["workers", "base-duration", "tie-break"])?;
+    let scheduler = scheduler(&args)?;
+    let graph = read_graph(args.input_file())?;
+
+    let schedule = scheduler
+        .schedule(&graph)
+        .context(ErrorKind::Parse, "Invalid dependency graph")?;
+    if args.flag("gantt") {
+        if schedule.finish_time > MAX_GANTT_SECONDS {
+            return Err(Error::usage(format!(
+                "Finish time {} is too large for --gantt, which prints a row per second",
+                schedule.finish_time
+            )));
+        }
+        print!("{}", gantt_table(&schedule));
+    } else {
+        print!("{}", steps_table(&schedule));
     }
+    println!("Finish Time: {}", schedule.finish_time);
 
-    (unprocessed_fwd, unprocessed_bwd)
+    Ok(())
 }
 
-fn part1(unprocessed_fwd: &[Step], unprocessed_bwd: &mut Vec<Step>) -> String {
-    let mut ready_list: BinaryHeap<StepId> = BinaryHeap::new();
+/// Prints the earliest and latest start time and the slack of every step if there were enough
+/// workers to start every step as soon as it is ready, followed by the critical path.
+///
+/// Usage: `critical-path <input file> [--base-duration <seconds>]`.
+fn critical_path_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &[], &["base-duration"])?;
+    let scheduler = scheduler(&args)?;
+    let graph = read_graph(args.input_file())?;
+
+    let critical_path = scheduler
+        .critical_path(&graph)
+        .context(ErrorKind::Parse, "Invalid dependency graph")?;
+    print!("{}", slack_table(&graph, &critical_path));
+    println!(
+        "Critical path: {} ({} seconds)",
+        critical_path
+            .path
+            .iter()
+            .map(|&i| graph.name(i))
+            .collect::<Vec<_>>()
+            .join(" -> "),
+        critical_path.length
+    );
+
+    Ok(())
+}
 
-    // Find all elements without dependency
-    for step in unprocessed_bwd
-        .iter()
-        .filter(|step| step.dependencies.is_empty())
-        .filter(|step| !unprocessed_fwd[StepId::idx(step.id)].dependents.is_empty())
-    {
-        if DEBUG {
-            println!("Pushing {} to ready list.", step.id as char);
-        }
-        ready_list.push(StepId(step.id));
-    }
+/// Prints the dependency graph in the Graphviz DOT format, with the critical path highlighted.
+///
+/// Usage: `dot <input file> [--base-duration <seconds>]`.
+fn dot_command(args: &[String]) -> Result<(), Error> {
+    let args = SubcommandArgs::parse(args, &[], &["base-duration"])?;
+    let scheduler = scheduler(&args)?;
+    let graph = read_graph(args.input_file())?;
 
-    // Process all elements in ready list
-    let mut order = String::new();
-    loop {
-        let next = match ready_list.pop() {
-            Some(id) => id,
-            None => {
-                if DEBUG {
-                    println!("No more elements. Aborting...");
-                }
-                break;
-            }
-        };
+    let critical_path = scheduler
+        .critical_path(&graph)
+        .context(ErrorKind::Parse, "Invalid dependency graph")?;
+    print!("{}", dot(&graph, &critical_path));
 
-        if DEBUG {
-            println!("Processing: {}", next.char());
-        }
+    Ok(())
+}
 
-        // Push to result string
-        order.push(next.char());
-
-        // Remove next as dependency and insert dependents that are ready (no further dependencies) into ready list
-        for step in unprocessed_fwd[next.to_idx()].dependents.iter() {
-            let dependencies = &mut unprocessed_bwd[StepId::idx(*step)].dependencies;
-            dependencies.retain(|&id| id != next.char() as u8);
-            if dependencies.is_empty() {
-                if DEBUG {
-                    println!("Pushing {} to ready list.", *step as char);
-                }
-                ready_list.push(StepId(*step));
-            } else if DEBUG {
-                println!("{} has more dependencies.", *step as char);
-            }
-        }
+/// Returns the scheduler configured by the options `--workers`, `--base-duration` and
+/// `--tie-break`, with the puzzle's values as defaults.
+fn scheduler(args: &SubcommandArgs) -> Result<Scheduler, Error> {
+    let workers = args.value("workers")?.unwrap_or(5);
+    if workers == 0 {
+        return Err(Error::usage("--workers must be positive"));
     }
-
-    order
+    let base_duration = args.value("base-duration")?.unwrap_or(60);
+    let tie_break = match args.value::<String>("tie-break")? {
+        Some(tie_break) => tie_break
+            .parse()
+            .context(ErrorKind::Usage, "Invalid value for --tie-break")?,
+        None => TieBreak::Name,
+    };
+    Ok(Scheduler::new(workers)
+        .base_duration(base_duration)
+        .tie_break(tie_break))
 }
 
-fn part2(unprocessed_fwd: &[Step], unprocessed_bwd: &mut Vec<Step>) -> (String, usize) {
-    let mut ready_list: BinaryHeap<Event> = BinaryHeap::new();
+fn read_graph(input_file: &str) -> Result<Graph, Error> {
+    let input: Vec<Instruction> = FileReader::new().read_from_file(input_file)?;
+    Ok(Graph::from_instructions(&input))
+}
 
-    // Find all elements without dependency
-    for step in unprocessed_bwd
-        .iter()
-        .filter(|step| step.dependencies.is_empty())
-        .filter(|step| !unprocessed_fwd[StepId::idx(step.id)].dependents.is_empty())
-    {
-        if DEBUG {
-            println!("Pushing {} to ready list with T = 0.", step.id as char);
-        }
-        ready_list.push(Event::new(0, StepId(step.id)));
+/// Returns a table with the worker, start and finish time of every step.
+fn steps_table(schedule: &Schedule) -> String {
+    let width = name_width(schedule.steps.iter().map(|step| step.step.as_str()), 4);
+    let mut table = format!(
+        "{:<width$}  Worker   Start  Finish\n",
+        "Step",
+        width = width
+    );
+    for step in schedule.steps.iter() {
+        let _ = writeln!(
+            table,
+            "{:<width$}  {:>6}  {:>6}  {:>6}",
+            step.step,
+            step.worker + 1,
+            step.start,
+            step.finish,
+            width = width
+        );
     }
+    table
+}
 
-    // Process all elements in ready list
-    let mut order = String::new();
-    let mut finish_time = 0;
-    let mut workers_idle = 5;
-    loop {
-        let mut next = match ready_list.pop() {
-            Some(id) => id,
-            None => {
-                if DEBUG {
-                    println!("No more elements. Aborting...");
-                }
-                break;
-            }
-        };
-
-        if workers_idle == 0 && !next.worker {
-            if DEBUG {
-                println!("[T = {}] All workers busy", next.time);
-            }
-            // TODO: maybe keep track of expected readyness of workers to have less useless events?
-            next.time += 1;
-            ready_list.push(next);
-            continue;
-        }
-
-        let next = next; // immutable from now on
-
-        if next.worker {
-            if DEBUG {
-                println!("[T = {}] Worker becomes ready", next.time);
-            }
-            workers_idle += 1;
-            continue;
-        }
-
-        if DEBUG {
-            println!("[T = {}] Processing: {}", next.time, next.id.char());
-        }
-        workers_idle -= 1;
-        // TODO: maybe keep track of expected readyness of workers to have less useless events?
-        ready_list.push(Event::worker(next.finish_time()));
-
-        // Update finish time
-        order.push(next.id.char());
-        if next.finish_time() > finish_time {
-            finish_time = next.finish_time();
-        }
+/// Returns a table of what every worker does in every second, and the steps done at its start,
+/// like in the puzzle description.
+fn gantt_table(schedule: &Schedule) -> String {
+    let headers: Vec<String> = (1..=schedule.workers)
+        .map(|worker| format!("Worker {}", worker))
+        .collect();
+    let width = name_width(
+        schedule
+            .steps
+            .iter()
+            .map(|step| step.step.as_str())
+            .chain(headers.iter().map(|header| header.as_str())),
+        0,
+    );
+    let mut finished: Vec<&ScheduledStep> = schedule.steps.iter().collect();
+    finished.sort_by_key(|step| step.finish);
+    let separator = if finished.iter().all(|step| step.step.chars().count() == 1) {
+        ""
+    } else {
+        " "
+    };
 
-        // Remove next as dependency and insert dependents that are ready (no further dependencies) into ready list
-        for step in unprocessed_fwd[next.id.to_idx()].dependents.iter() {
-            let dependencies = &mut unprocessed_bwd[StepId::idx(*step)].dependencies;
-            dependencies.retain(|&id| id != next.id.char() as u8);
-            if dependencies.is_empty() {
-                if DEBUG {
-                    println!(
-                        "Pushing {} to ready list with T = {}.",
-                        *step as char,
-                        next.finish_time()
-                    );
-                }
-                ready_list.push(Event::new(next.finish_time(), StepId(*step)));
-            } else if DEBUG {
-                println!("{} has more dependencies.", *step as char);
-            }
+    let mut table = String::from("Second");
+    for header in headers.iter() {
+        let _ = write!(table, "   {:^width$}", header, width = width);
+    }
+    table.push_str("   Done\n");
+    for time in 0..=schedule.finish_time {
+        let mut row = format!("{:>6}", time);
+        for worker in 0..schedule.workers {
+            let step = schedule
+                .step_at(worker, time)
+                .map_or(".", |step| &step.step);
+            let _ = write!(row, "   {:^width$}", step, width = width);
         }
+        let done: Vec<&str> = finished
+            .iter()
+            .take_while(|step| step.finish <= time)
+            .map(|step| step.step.as_str())
+            .collect();
+        let _ = write!(row, "   {}", done.join(separator));
+        table.push_str(row.trim_end());
+        table.push('\n');
     }
-
-    (order, finish_time)
+    table
 }
 
-#[derive(Debug)]
-struct StepId(u8);
-
-impl Ord for StepId {
-    fn cmp(&self, other: &StepId) -> Ordering {
-        // We want to implement a Min Heap, so invert normal sort order
-        other.0.cmp(&self.0)
+/// Returns a table with the duration, earliest and latest start time and slack of every step,
+/// with critical steps marked by `*`.
+fn slack_table(graph: &Graph, critical_path: &CriticalPath) -> String {
+    let width = name_width((0..graph.len()).map(|i| graph.name(i)), 4);
+    let mut order: Vec<usize> = (0..graph.len()).collect();
+    order.sort_by_key(|&i| (critical_path.timings[i].earliest_start, graph.name(i)));
+
+    let mut table = format!(
+        "{:<width$}  Duration  Earliest  Latest  Slack\n",
+        "Step",
+        width = width
+    );
+    for i in order {
+        let timing = &critical_path.timings[i];
+        let _ = writeln!(
+            table,
+            "{:<width$}  {:>8}  {:>8}  {:>6}  {:>5}{}",
+            graph.name(i),
+            timing.duration,
+            timing.earliest_start,
+            timing.latest_start,
+            timing.slack(),
+            if timing.is_critical() { " *" } else { "" },
+            width = width
+        );
     }
+    table
 }
 
-impl PartialOrd for StepId {
-    fn partial_cmp(&self, other: &StepId) -> Option<Ordering> {
-        Some(self.cmp(other))
+/// Returns the dependency graph in the Graphviz DOT format. Nodes are labelled with their
+/// duration, and the steps and dependencies of the critical path are drawn in red.
+fn dot(graph: &Graph, critical_path: &CriticalPath) -> String {
+    let escape = |name: &str| name.replace('\\', "\\\\").replace('"', "\\\"");
+    let quote = |name: &str| format!("\"{}\"", escape(name));
+    let on_path = |i: usize| critical_path.path.contains(&i);
+
+    let mut dot = String::from("digraph steps {\n    rankdir=LR;\n");
+    for i in 0..graph.len() {
+        let timing = &critical_path.timings[i];
+        let _ = writeln!(
+            dot,
+            "    {} [label=\"{}\\n{}s, slack {}s\"{}];",
+            quote(graph.name(i)),
+            escape(graph.name(i)),
+            timing.duration,
+            timing.slack(),
+            if on_path(i) { ", color=red" } else { "" }
+        );
     }
-}
-
-impl PartialEq for StepId {
-    fn eq(&self, other: &StepId) -> bool {
-        self.0 == other.0
+    for i in 0..graph.len() {
+        for &dependent in graph.dependents(i) {
+            let critical = critical_path
+                .path
+                .windows(2)
+                .any(|pair| pair == [i, dependent]);
+            let _ = writeln!(
+                dot,
+                "    {} -> {}{};",
+                quote(graph.name(i)),
+                quote(graph.name(dependent)),
+                if critical { " [color=red]" } else { "" }
+            );
+        }
     }
+    dot.push_str("}\n");
+    dot
 }
 
-impl Eq for StepId {}
+/// Returns the width of the widest of `names`, but at least `min_width`.
+fn name_width<'a, I: Iterator<Item = &'a str>>(names: I, min_width: usize) -> usize {
+    names
+        .map(|name| name.chars().count())
+        .fold(min_width, usize::max)
+}
 
-impl StepId {
-    fn idx(letter: u8) -> usize {
-        (letter - b'A') as usize
+/// Generates a random dependency DAG over `size` steps, named A to Z, then AA, AB and so on.
+///
+/// Every step takes part in at least one dependency, and about a quarter of the steps get an
+/// explicit duration.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut steps: Vec<String> = (0..size.max(2)).map(step_name).collect();
+    rng.shuffle(&mut steps);
+
+    // Edges always point forward in `steps`, which makes the graph acyclic
+    let mut edges = HashSet::new();
+    for i in 1..steps.len() {
+        edges.insert((rng.range_usize(0, i), i));
     }
-
-    fn to_idx(&self) -> usize {
-        StepId::idx(self.0)
+    for _ in 0..steps.len() {
+        let a = rng.range_usize(0, steps.len());
+        let b = rng.range_usize(0, steps.len());
+        if a != b {
+            edges.insert((a.min(b), a.max(b)));
+        }
     }
 
-    fn char(&self) -> char {
-        self.0 as char
+    let mut edges: Vec<_> = edges.into_iter().collect();
+    edges.sort_unstable();
+    let mut instructions: Vec<Instruction> = edges
+        .into_iter()
+        .map(|(before, step)| {
+            Instruction::Dependency(Dependency {
+                before: steps[before].clone(),
+                step: steps[step].clone(),
+            })
+        })
+        .collect();
+    for step in steps.iter() {
+        if rng.chance(0.25) {
+            instructions.push(Instruction::Duration(StepDuration {
+                step: step.clone(),
+                seconds: rng.range_usize(1, 100) as u64,
+            }));
+        }
     }
-}
 
-#[derive(Debug)]
-struct Event {
-    time: usize,
-    id: StepId,
-    worker: bool,
+    rng.shuffle(&mut instructions);
+    instructions
+        .iter()
+        .map(|instruction| format!("{}\n", instruction))
+        .collect()
 }
 
-impl Ord for Event {
-    fn cmp(&self, other: &Event) -> Ordering {
-        // We want to implement a Min Heap, so invert normal sort order
-        if self.time == other.time {
-            if self.worker && !other.worker {
-                Ordering::Greater
-            } else if !self.worker && other.worker {
-                Ordering::Less
-            } else if self.worker && other.worker {
-                Ordering::Equal
-            } else {
-                self.id.cmp(&other.id)
-            }
-        } else {
-            other.time.cmp(&self.time)
+/// Returns the `i`-th step name in the sequence A, ..., Z, AA, AB, ..., ZZ, AAA, ...
+fn step_name(i: usize) -> String {
+    let mut name = Vec::new();
+    let mut i = i;
+    loop {
+        name.push(b'A' + (i % 26) as u8);
+        if i < 26 {
+            break;
         }
+        i = i / 26 - 1;
     }
+    name.reverse();
+    String::from_utf8(name).unwrap()
 }
 
-impl PartialOrd for Event {
-    fn partial_cmp(&self, other: &Event) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl PartialEq for Event {
-    fn eq(&self, other: &Event) -> bool {
-        self.time == other.time && self.id == other.id
+    fn read_graph() -> Graph {
+        let input: Vec<Instruction> = FileReader::new().read_from_file("input.txt").unwrap();
+        Graph::from_instructions(&input)
     }
-}
 
-impl Eq for Event {}
+    #[test]
+    fn test_part1() {
+        let order = Scheduler::new(1)
+            .schedule(&read_graph())
+            .unwrap()
+            .order()
+            .concat();
+        assert_eq!("CQSWKZFJONPBEUMXADLYIGVRHT", order);
+    }
 
-impl Event {
-    fn new(time: usize, id: StepId) -> Self {
-        Self {
-            time,
-            id,
-            worker: false,
-        }
+    #[test]
+    fn test_part2() {
+        let schedule = Scheduler::new(5)
+            .base_duration(60)
+            .schedule(&read_graph())
+            .unwrap();
+        assert_eq!(914, schedule.finish_time);
     }
 
-    fn worker(time: usize) -> Self {
-        Self {
-            time,
-            id: StepId(0),
-            worker: true,
+    fn example() -> Graph {
+        let mut graph = Graph::new();
+        for (before, step) in &[
+            ("C", "A"),
+            ("C", "F"),
+            ("A", "B"),
+            ("A", "D"),
+            ("B", "E"),
+            ("D", "E"),
+            ("F", "E"),
+        ] {
+            graph.add_dependency(before, step);
         }
+        graph
     }
 
-    fn finish_time(&self) -> usize {
-        self.time + 60 + self.id.to_idx() + 1
+    #[test]
+    fn test_gantt_table() {
+        let schedule = Scheduler::new(2).schedule(&example()).unwrap();
+        let table = gantt_table(&schedule);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(17, lines.len());
+        assert_eq!("Second   Worker 1   Worker 2   Done", lines[0]);
+        assert_eq!("     0      C          .", lines[1]);
+        assert_eq!("     3      A          F       C", lines[4]);
+        assert_eq!("     9      D          .       CABF", lines[10]);
+        assert_eq!("    15      .          .       CABFDE", lines[16]);
     }
-}
 
-#[derive(Debug, Clone)]
-struct Step {
-    id: u8,
-    dependencies: Vec<u8>,
-    dependents: Vec<u8>,
-}
-
-impl Step {
-    fn new(id: u8) -> Self {
-        Self {
-            id,
-            dependencies: Vec::new(),
-            dependents: Vec::new(),
-        }
+    #[test]
+    fn test_slack_table() {
+        let graph = example();
+        let critical_path = Scheduler::new(1)
+            .base_duration(60)
+            .critical_path(&graph)
+            .unwrap();
+        assert_eq!(
+            "Step  Duration  Earliest  Latest  Slack
+C           63         0       0      0 *
+A           61        63      63      0 *
+F           66        63     122     59
+B           62       124     126      2
+D           64       124     124      0 *
+E           65       188     188      0 *
+",
+            slack_table(&graph, &critical_path)
+        );
     }
-}
 
-#[derive(Debug, Clone)]
-struct Dependency {
-    before: u8,
-    step: u8,
+    #[test]
+    fn test_dot() {
+        let mut graph = Graph::new();
+        graph.add_dependency("a\"b", "c");
+        graph.set_duration("c", 2);
+        let critical_path = Scheduler::new(1)
+            .base_duration(60)
+            .critical_path(&graph)
+            .unwrap();
+        assert_eq!(
+            r#"digraph steps {
+    rankdir=LR;
+    "a\"b" [label="a\"b\n60s, slack 0s", color=red];
+    "c" [label="c\n2s, slack 0s", color=red];
+    "a\"b" -> "c" [color=red];
 }
-
-impl FromStr for Dependency {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let letters: Vec<char> = s
-            .split_whitespace()
-            .map(|s| s.trim())
-            .filter(|&s| s.len() == 1)
-            .map(|s| s.chars().next().unwrap())
-            .collect();
-
-        if letters.len() != 2 {
-            return Err(String::from("input does not match format"));
-        }
-
-        Ok(Self {
-            before: letters[0] as u8,
-            step: letters[1] as u8,
-        })
+"#,
+            dot(&graph, &critical_path)
+        );
     }
-}
 
-impl std::fmt::Display for Dependency {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} => {}", self.before as char, self.step as char)
+    #[test]
+    fn test_generate() {
+        let input = generate(&mut Rng::new(1), 100);
+        let instructions: Vec<Instruction> = input.lines().map(|l| l.parse().unwrap()).collect();
+        assert!(instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Duration(_))));
+        let graph = Graph::from_instructions(&instructions);
+        assert_eq!(100, graph.len());
+        assert!(graph.step("CV").is_some());
+        let schedule = Scheduler::new(1).schedule(&graph).unwrap();
+        assert_eq!(100, schedule.order().len());
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_part1() {
-        let dependencies: Vec<Dependency> = FileReader::new().read_from_file("input.txt").unwrap();
-        let (unprocessed_fwd, mut unprocessed_bwd) = create_dependencies(&dependencies);
-        let order = part1(&unprocessed_fwd, &mut unprocessed_bwd);
-        assert_eq!("CQSWKZFJONPBEUMXADLYIGVRHT", order);
+    fn test_step_name() {
+        let names: Vec<String> = [0, 25, 26, 27, 701, 702]
+            .iter()
+            .map(|&i| step_name(i))
+            .collect();
+        assert_eq!(vec!["A", "Z", "AA", "AB", "ZZ", "AAA"], names);
     }
 
     #[test]
-    fn test_part2() {
-        let dependencies: Vec<Dependency> = FileReader::new().read_from_file("input.txt").unwrap();
-        let (unprocessed_fwd, mut unprocessed_bwd) = create_dependencies(&dependencies);
-        let (_, finish_time) = part2(&unprocessed_fwd, &mut unprocessed_bwd);
-        assert_eq!(914, finish_time);
+    fn test_solve_generated() {
+        for &size in &[1, 2, 10, 100] {
+            for seed in 0..5 {
+                cli::solve_generated(7, solve, generate, size, seed).unwrap();
+            }
+        }
     }
 }
@@ -0,0 +1,154 @@
+//! Circular list with a cursor, where moving the cursor by a few positions and inserting or
+//! removing at the cursor take constant time.
+//!
+//! # Examples
+//! ```
+//! use util::circular::CircularList;
+//!
+//! let mut list: CircularList<u32> = (1..=5).collect();
+//! assert_eq!(Some(&1), list.current());
+//! list.move_by(-2);
+//! assert_eq!(Some(&4), list.current());
+//! list.insert(9);
+//! assert_eq!(vec![9, 4, 5, 1, 2, 3], list.iter().copied().collect::<Vec<_>>());
+//! assert_eq!(Some(9), list.remove());
+//! assert_eq!(Some(&4), list.current());
+//! ```
+
+use std::collections::VecDeque;
+use std::iter::FromIterator;
+
+/// Circular list with a cursor on the current element.
+///
+/// Positions are counted clockwise, i.e. in the order in which `iter` returns the elements. The
+/// list is backed by a `VecDeque` whose front is the current element, so moving by `n`
+/// positions takes `O(min(n, len - n))` time.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CircularList<T> {
+    deque: VecDeque<T>,
+}
+
+impl<T> CircularList<T> {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self {
+            deque: VecDeque::new(),
+        }
+    }
+
+    /// Creates an empty list with space for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            deque: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    /// Returns whether the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.deque.is_empty()
+    }
+
+    /// Returns the current element, or `None` if the list is empty.
+    pub fn current(&self) -> Option<&T> {
+        self.deque.front()
+    }
+
+    /// Returns the current element mutably, or `None` if the list is empty.
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        self.deque.front_mut()
+    }
+
+    /// Moves the cursor `offset` positions clockwise (counter-clockwise if `offset` is
+    /// negative), wrapping around as often as necessary. Does nothing if the list is empty.
+    pub fn move_by(&mut self, offset: isize) {
+        if self.deque.is_empty() {
+            return;
+        }
+        let len = self.deque.len() as isize;
+        let offset = offset.rem_euclid(len) as usize;
+        if offset > 0 {
+            self.deque.rotate_left(offset);
+        }
+    }
+
+    /// Inserts `value` just counter-clockwise of the current element and makes it the current
+    /// element.
+    pub fn insert(&mut self, value: T) {
+        self.deque.push_front(value);
+    }
+
+    /// Removes the current element and returns it, making the next element clockwise the
+    /// current one. Returns `None` if the list is empty.
+    pub fn remove(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    /// Returns an iterator over the elements, starting with the current one and going
+    /// clockwise.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.deque.iter()
+    }
+}
+
+impl<T> FromIterator<T> for CircularList<T> {
+    /// Creates a list of the elements in clockwise order, with the cursor on the first one.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            deque: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elements(list: &CircularList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_move() {
+        let mut list: CircularList<i32> = (0..5).collect();
+        list.move_by(2);
+        assert_eq!(vec![2, 3, 4, 0, 1], elements(&list));
+        list.move_by(-3);
+        assert_eq!(vec![4, 0, 1, 2, 3], elements(&list));
+        list.move_by(11);
+        assert_eq!(Some(&0), list.current());
+        list.move_by(-10);
+        assert_eq!(Some(&0), list.current());
+        list.move_by(isize::MIN);
+        assert_eq!(5, list.len());
+
+        let mut empty: CircularList<i32> = CircularList::new();
+        empty.move_by(3);
+        assert_eq!(None, empty.current());
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut list = CircularList::with_capacity(4);
+        assert!(list.is_empty());
+        assert_eq!(None, list.remove());
+
+        list.insert(0);
+        list.insert(1);
+        assert_eq!(vec![1, 0], elements(&list));
+        list.move_by(1);
+        list.insert(2);
+        assert_eq!(vec![2, 0, 1], elements(&list));
+        *list.current_mut().unwrap() = 3;
+
+        assert_eq!(Some(3), list.remove());
+        assert_eq!(Some(&0), list.current());
+        assert_eq!(Some(0), list.remove());
+        assert_eq!(Some(1), list.remove());
+        assert!(list.is_empty());
+    }
+}
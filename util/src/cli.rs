@@ -0,0 +1,547 @@
+//! Command line handling shared by the day binaries.
+//!
+//! Every binary is invoked as `<binary> <input file> [--format text|json] [--diagnostics]`.
+//! Solvers report their answers (and any intermediate output) through `Output`, which either
+//! prints them as prose (default) or as one JSON record per line:
+//! ```text
+//! {"day":1,"part":1,"answer":"580","elapsed_ms":0.412}
+//! ```
+//! In JSON mode, diagnostics (intermediate output) are only emitted if `--diagnostics` is given,
+//! as records of the form `{"day":1,"diagnostic":"..."}`.
+//!
+//! Binaries registered with `run_with_generator` additionally accept
+//! `<binary> generate [--size N] [--seed S]`, which writes a synthetic puzzle input to stdout.
+//! The same seed always yields the same input; if no seed is given, one is chosen from the
+//! current time and written to stderr.
+//!
+//! Binaries registered with `run_with_subcommands` can provide further, day specific
+//! subcommands (e.g. reports or exports), invoked as `<binary> <subcommand> <arguments>`. Their
+//! arguments can be parsed with `SubcommandArgs`.
+//!
+//! # Examples
+//! ```no_run
+//! use util::cli::{self, Output};
+//! use util::error::Error;
+//! use util::input::{FileReader, FromFile};
+//!
+//! fn main() {
+//!     cli::run(1, solve);
+//! }
+//!
+//! fn solve(input_file: &str, output: &Output) -> Result<(), Error> {
+//!     let input: Vec<i64> = FileReader::new().read_from_file(input_file)?;
+//!     let sum: i64 = input.iter().sum();
+//!     output.answer(1, sum, format!("Sum: {}", sum));
+//!     Ok(())
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::{Display, Write};
+use std::fs;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::Error;
+use crate::random::Rng;
+
+/// Size passed to input generators if `--size` is not given.
+pub const DEFAULT_GENERATE_SIZE: usize = 100;
+
+/// Function generating a puzzle input of (roughly) the given size.
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+/// Function handling a day specific subcommand, called with the arguments following its name.
+pub type Subcommand = fn(&[String]) -> Result<(), Error>;
+
+/// Format in which the answers are printed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Free-form prose, as chosen by the solver.
+    Text,
+    /// One JSON record per line.
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            s => Err(Error::usage(format!(
+                "Unknown format \"{}\" (expected \"text\" or \"json\")",
+                s
+            ))),
+        }
+    }
+}
+
+/// Sink for the answers and diagnostics of a solver.
+#[derive(Debug)]
+pub struct Output {
+    day: u8,
+    format: Format,
+    diagnostics: bool,
+    last_answer: Cell<Instant>,
+}
+
+impl Output {
+    /// Create new `Output` for the given day.
+    pub fn new(day: u8, format: Format, diagnostics: bool) -> Self {
+        Self {
+            day,
+            format,
+            diagnostics,
+            last_answer: Cell::new(Instant::now()),
+        }
+    }
+
+    /// Returns the format in which the answers are printed.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Reports the answer to `part` of the puzzle. In text mode, `text` is printed; in JSON mode
+    /// a record containing `answer` and the time elapsed since the previous answer (or since the
+    /// start of the solver) is printed.
+    pub fn answer<A: Display, T: Display>(&self, part: u8, answer: A, text: T) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_answer.replace(now));
+
+        match self.format {
+            Format::Text => println!("{}", text),
+            Format::Json => println!(
+                "{{\"day\":{},\"part\":{},\"answer\":{},\"elapsed_ms\":{:.3}}}",
+                self.day,
+                part,
+                json_string(&answer),
+                elapsed.as_secs_f64() * 1000.0
+            ),
+        }
+    }
+
+    /// Reports intermediate output. Always printed in text mode, only printed in JSON mode if
+    /// diagnostics are enabled.
+    pub fn diagnostic<D: Display>(&self, message: D) {
+        match self.format {
+            Format::Text => println!("{}", message),
+            Format::Json if self.diagnostics => println!(
+                "{{\"day\":{},\"diagnostic\":{}}}",
+                self.day,
+                json_string(&message)
+            ),
+            Format::Json => {}
+        }
+    }
+
+    /// Returns whether diagnostics are printed. Can be used to skip expensive formatting.
+    pub fn diagnostics_enabled(&self) -> bool {
+        self.format == Format::Text || self.diagnostics
+    }
+}
+
+/// Formats `value` as a JSON string literal.
+fn json_string<D: Display>(value: &D) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.to_string().chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Command line arguments common to all day binaries.
+#[derive(Debug, PartialEq)]
+struct Args {
+    input_file: String,
+    format: Format,
+    diagnostics: bool,
+}
+
+impl Args {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, Error> {
+        let mut input_file = None;
+        let mut format = Format::Text;
+        let mut diagnostics = false;
+
+        while let Some(arg) = args.next() {
+            if arg == "--format" {
+                format = args
+                    .next()
+                    .ok_or_else(|| Error::usage("Missing value for --format"))?
+                    .parse()?;
+            } else if let Some(value) = arg.strip_prefix("--format=") {
+                format = value.parse()?;
+            } else if arg == "--diagnostics" {
+                diagnostics = true;
+            } else if arg.starts_with("--") {
+                return Err(Error::usage(format!("Unknown option {}", arg)));
+            } else if input_file.is_none() {
+                input_file = Some(arg);
+            } else {
+                return Err(Error::usage(format!("Unexpected argument {}", arg)));
+            }
+        }
+
+        Ok(Self {
+            input_file: input_file.ok_or_else(|| Error::usage("Please supply input file!"))?,
+            format,
+            diagnostics,
+        })
+    }
+}
+
+/// Arguments of the `generate` subcommand.
+#[derive(Debug, PartialEq)]
+struct GenerateArgs {
+    size: usize,
+    seed: Option<u64>,
+}
+
+impl GenerateArgs {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, Error> {
+        let mut size = DEFAULT_GENERATE_SIZE;
+        let mut seed = None;
+
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.find('=') {
+                Some(index) => (arg[..index].to_string(), Some(arg[index + 1..].to_string())),
+                None => (arg, None),
+            };
+            if name != "--size" && name != "--seed" {
+                return Err(Error::usage(format!("Unexpected argument {}", name)));
+            }
+            let value = value
+                .or_else(|| args.next())
+                .ok_or_else(|| Error::usage(format!("Missing value for {}", name)))?;
+            let value: u64 = value
+                .parse()
+                .map_err(|_| Error::usage(format!("Invalid value for {}: {}", name, value)))?;
+            if name == "--size" {
+                if value == 0 {
+                    return Err(Error::usage("Invalid value for --size: must be positive"));
+                }
+                size = value as usize;
+            } else {
+                seed = Some(value);
+            }
+        }
+
+        Ok(Self { size, seed })
+    }
+}
+
+/// Arguments of a day specific subcommand: an input file, flags (`--name`) and options with a
+/// value (`--name value` or `--name=value`), in any order.
+#[derive(Debug, PartialEq)]
+pub struct SubcommandArgs {
+    input_file: String,
+    options: HashMap<String, Option<String>>,
+}
+
+impl SubcommandArgs {
+    /// Parses `args`, accepting the given `flags` and `options` (names without leading `--`).
+    pub fn parse(args: &[String], flags: &[&str], options: &[&str]) -> Result<Self, Error> {
+        let mut input_file = None;
+        let mut parsed = HashMap::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = match arg.strip_prefix("--") {
+                Some(name) => name,
+                None if input_file.is_none() => {
+                    input_file = Some(arg.clone());
+                    continue;
+                }
+                None => return Err(Error::usage(format!("Unexpected argument {}", arg))),
+            };
+            let (name, value) = match name.find('=') {
+                Some(index) => (&name[..index], Some(name[index + 1..].to_string())),
+                None => (name, None),
+            };
+
+            if flags.contains(&name) && value.is_none() {
+                parsed.insert(name.to_string(), None);
+            } else if options.contains(&name) {
+                let value = value
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| Error::usage(format!("Missing value for --{}", name)))?;
+                parsed.insert(name.to_string(), Some(value));
+            } else {
+                return Err(Error::usage(format!("Unknown option {}", arg)));
+            }
+        }
+
+        Ok(Self {
+            input_file: input_file.ok_or_else(|| Error::usage("Please supply input file!"))?,
+            options: parsed,
+        })
+    }
+
+    /// Returns the input file.
+    pub fn input_file(&self) -> &str {
+        &self.input_file
+    }
+
+    /// Returns whether the flag `name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    /// Returns the value of option `name` if it was given, parsed as `T`.
+    pub fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, Error> {
+        match self.options.get(name) {
+            Some(Some(value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| Error::usage(format!("Invalid value for --{}: {}", name, value))),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Command selected on the command line.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Solve(Args),
+    Generate(GenerateArgs),
+    /// Day specific subcommand (index into the registered subcommands) and its arguments.
+    Subcommand(usize, Vec<String>),
+}
+
+impl Command {
+    fn parse<I: Iterator<Item = String>>(args: I, subcommands: &[&str]) -> Result<Self, Error> {
+        let mut args = args.peekable();
+        let first = args.peek().map(String::as_str);
+        if first == Some("generate") {
+            args.next();
+            Ok(Command::Generate(GenerateArgs::parse(args)?))
+        } else if let Some(index) = subcommands.iter().position(|&name| Some(name) == first) {
+            args.next();
+            Ok(Command::Subcommand(index, args.collect()))
+        } else {
+            Ok(Command::Solve(Args::parse(args)?))
+        }
+    }
+}
+
+/// Runs `solve` for the given day with the input file passed on the command line.
+///
+/// If `solve` fails (or the command line is invalid), the error and its causes are written to
+/// stderr and the process exits with the exit code corresponding to the kind of the error.
+pub fn run<F>(day: u8, solve: F)
+where
+    F: FnOnce(&str, &Output) -> Result<(), Error>,
+{
+    run_with_subcommands(day, solve, None, &[]);
+}
+
+/// Like `run`, but additionally supports the `generate` subcommand using `generate`.
+pub fn run_with_generator<F>(day: u8, solve: F, generate: Generator)
+where
+    F: FnOnce(&str, &Output) -> Result<(), Error>,
+{
+    run_with_subcommands(day, solve, Some(generate), &[]);
+}
+
+/// Like `run`, but additionally supports the `generate` subcommand if `generate` is given, and
+/// the day specific `subcommands`, given as pairs of name and handler.
+pub fn run_with_subcommands<F>(
+    day: u8,
+    solve: F,
+    generate: Option<Generator>,
+    subcommands: &[(&str, Subcommand)],
+) where
+    F: FnOnce(&str, &Output) -> Result<(), Error>,
+{
+    let names: Vec<&str> = subcommands.iter().map(|&(name, _)| name).collect();
+    let result = Command::parse(env::args().skip(1), &names).and_then(|command| match command {
+        Command::Solve(args) => {
+            let output = Output::new(day, args.format, args.diagnostics);
+            solve(&args.input_file, &output)
+        }
+        Command::Generate(args) => {
+            let generate = generate.ok_or_else(|| {
+                Error::usage(format!("No input generator available for day {}", day))
+            })?;
+            let seed = args.seed.unwrap_or_else(|| {
+                let seed = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_nanos() as u64)
+                    .unwrap_or_default();
+                eprintln!("Seed: {}", seed);
+                seed
+            });
+            print!("{}", generate(&mut Rng::new(seed), args.size));
+            Ok(())
+        }
+        Command::Subcommand(index, args) => (subcommands[index].1)(&args),
+    });
+
+    if let Err(e) = result {
+        e.report();
+        std::process::exit(e.kind().exit_code());
+    }
+}
+
+/// Runs `solve` on the input generated by `generate` for `size` and `seed`, without printing
+/// the answers.
+///
+/// Meant for tests checking that a generator only produces inputs its solver can handle. The
+/// input is passed to `solve` in a temporary file.
+pub fn solve_generated<F>(
+    day: u8,
+    solve: F,
+    generate: Generator,
+    size: usize,
+    seed: u64,
+) -> Result<(), Error>
+where
+    F: FnOnce(&str, &Output) -> Result<(), Error>,
+{
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let input = generate(&mut Rng::new(seed), size);
+    let path = env::temp_dir().join(format!(
+        "day{}-generated-{}-{}.txt",
+        day,
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&path, input)?;
+    let result = solve(
+        &path.to_string_lossy(),
+        &Output::new(day, Format::Json, false),
+    );
+    let _ = fs::remove_file(&path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, Error> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            Args {
+                input_file: String::from("input.txt"),
+                format: Format::Text,
+                diagnostics: false,
+            },
+            args(&["input.txt"]).unwrap()
+        );
+        assert_eq!(
+            Args {
+                input_file: String::from("input.txt"),
+                format: Format::Json,
+                diagnostics: true,
+            },
+            args(&["--format", "json", "input.txt", "--diagnostics"]).unwrap()
+        );
+        assert_eq!(
+            Format::Json,
+            args(&["input.txt", "--format=json"]).unwrap().format
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["input.txt", "--format"]).is_err());
+        assert!(args(&["input.txt", "--format", "xml"]).is_err());
+        assert!(args(&["input.txt", "--verbose"]).is_err());
+        assert!(args(&["input.txt", "other.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let command = |a: &[&str]| Command::parse(a.iter().map(|s| s.to_string()), &["report"]);
+
+        assert_eq!(
+            Command::Solve(args(&["generate.txt"]).unwrap()),
+            command(&["generate.txt"]).unwrap()
+        );
+        assert_eq!(
+            Command::Generate(GenerateArgs {
+                size: DEFAULT_GENERATE_SIZE,
+                seed: None,
+            }),
+            command(&["generate"]).unwrap()
+        );
+        assert_eq!(
+            Command::Generate(GenerateArgs {
+                size: 10,
+                seed: Some(42),
+            }),
+            command(&["generate", "--seed", "42", "--size=10"]).unwrap()
+        );
+
+        assert!(command(&["generate", "--size"]).is_err());
+        assert!(command(&["generate", "--size", "-1"]).is_err());
+        assert!(command(&["generate", "--size", "0"]).is_err());
+        assert!(command(&["generate", "input.txt"]).is_err());
+
+        assert_eq!(
+            Command::Subcommand(0, vec![String::from("input.txt"), String::from("--csv")]),
+            command(&["report", "input.txt", "--csv"]).unwrap()
+        );
+        assert_eq!(
+            Command::Solve(args(&["export"]).unwrap()),
+            command(&["export"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_subcommand_args() {
+        let parse = |a: &[&str]| {
+            let a: Vec<String> = a.iter().map(|s| s.to_string()).collect();
+            SubcommandArgs::parse(&a, &["csv"], &["scale", "output"])
+        };
+
+        let args = parse(&["--scale", "3", "input.txt", "--csv", "--output=out.txt"]).unwrap();
+        assert_eq!("input.txt", args.input_file());
+        assert!(args.flag("csv"));
+        assert_eq!(Some(3), args.value::<u32>("scale").unwrap());
+        assert_eq!(Some(String::from("out.txt")), args.value("output").unwrap());
+
+        let args = parse(&["input.txt"]).unwrap();
+        assert!(!args.flag("csv"));
+        assert_eq!(None, args.value::<u32>("scale").unwrap());
+
+        assert!(parse(&["input.txt", "--scale", "x"])
+            .unwrap()
+            .value::<u32>("scale")
+            .is_err());
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["input.txt", "--scale"]).is_err());
+        assert!(parse(&["input.txt", "--csv=yes"]).is_err());
+        assert!(parse(&["input.txt", "--verbose"]).is_err());
+        assert!(parse(&["input.txt", "other.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!("\"42\"", json_string(&42));
+        assert_eq!(
+            "\"a \\\"b\\\"\\n\\\\c\\u0001\"",
+            json_string(&"a \"b\"\n\\c\u{1}")
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Common error type for the day binaries.
+//!
+//! Solvers return `Result<_, Error>` and propagate failures with `?`. Errors from `util::input`
+//! and `std::io` convert automatically, other errors can be wrapped using `Context`:
+//! ```
+//! use util::error::{Context, Error, ErrorKind};
+//!
+//! fn parse_serial_number(s: &str) -> Result<usize, Error> {
+//!     s.trim()
+//!         .parse::<usize>()
+//!         .context(ErrorKind::Parse, "Invalid grid serial number")
+//! }
+//!
+//! assert_eq!(18, parse_serial_number("18\n").unwrap());
+//! assert_eq!(ErrorKind::Parse, parse_serial_number("x").unwrap_err().kind());
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+
+/// Boxed error that can be used as the cause of an `Error`.
+pub type Source = Box<dyn StdError + Send + Sync + 'static>;
+
+/// Category of an `Error`. Determines the exit code of the binary.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The binary was invoked with invalid arguments.
+    Usage,
+    /// The input could not be read.
+    Io,
+    /// The input does not correspond to the expected format.
+    Parse,
+    /// The solver failed on otherwise valid input.
+    Runtime,
+}
+
+impl ErrorKind {
+    /// Returns the exit code the binary terminates with on an error of this kind.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Usage => 2,
+            ErrorKind::Io => 3,
+            ErrorKind::Parse => 4,
+            ErrorKind::Runtime => 5,
+        }
+    }
+}
+
+/// Error returned by solvers, consisting of a kind, a message and an optional cause.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    source: Option<Source>,
+}
+
+impl Error {
+    /// Create new `Error` without a cause.
+    pub fn new<M: Into<String>>(kind: ErrorKind, message: M) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create new `Error` of kind `ErrorKind::Usage`.
+    pub fn usage<M: Into<String>>(message: M) -> Self {
+        Self::new(ErrorKind::Usage, message)
+    }
+
+    /// Create new `Error` of kind `ErrorKind::Parse`.
+    pub fn parse<M: Into<String>>(message: M) -> Self {
+        Self::new(ErrorKind::Parse, message)
+    }
+
+    /// Create new `Error` of kind `ErrorKind::Runtime`.
+    pub fn runtime<M: Into<String>>(message: M) -> Self {
+        Self::new(ErrorKind::Runtime, message)
+    }
+
+    /// Sets the cause of `self`.
+    pub fn with_source<S: Into<Source>>(self, source: S) -> Self {
+        Self {
+            source: Some(source.into()),
+            ..self
+        }
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns an iterator over the causes of the error, starting with the immediate cause.
+    pub fn causes(&self) -> impl Iterator<Item = &(dyn StdError + 'static)> {
+        let mut next = self.source();
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = current.source();
+            Some(current)
+        })
+    }
+
+    /// Writes the error and its cause chain to stderr.
+    pub fn report(&self) {
+        eprintln!("Error: {}", self);
+        for cause in self.causes() {
+            eprintln!("  caused by: {}", cause);
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn StdError + 'static))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::new(ErrorKind::Io, "Error reading input").with_source(error)
+    }
+}
+
+impl<E: fmt::Display> From<crate::input::Error<E>> for Error {
+    fn from(error: crate::input::Error<E>) -> Self {
+        let parse_error = Error::new(ErrorKind::Parse, "Error reading input");
+        match error {
+            crate::input::Error::IoError(e) => Error::from(e),
+            crate::input::Error::ParseError(e) => parse_error.with_source(e.to_string()),
+            crate::input::Error::FormatError(s) => parse_error.with_source(s),
+        }
+    }
+}
+
+/// Extension trait to wrap the error of a `Result` (or a `None`) into an `Error`.
+pub trait Context<T> {
+    /// Converts the error into an `Error` of the given kind and message, with the original
+    /// error as its cause.
+    fn context<M: Into<String>>(self, kind: ErrorKind, message: M) -> Result<T, Error>;
+}
+
+impl<T, E> Context<T> for Result<T, E>
+where
+    E: Into<Source>,
+{
+    fn context<M: Into<String>>(self, kind: ErrorKind, message: M) -> Result<T, Error> {
+        self.map_err(|e| Error::new(kind, message).with_source(e))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context<M: Into<String>>(self, kind: ErrorKind, message: M) -> Result<T, Error> {
+        self.ok_or_else(|| Error::new(kind, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cause_chain() {
+        let inner: Result<(), String> = Err(String::from("unexpected token 'x'"));
+        let error = inner
+            .context(ErrorKind::Parse, "Invalid instruction")
+            .context(ErrorKind::Runtime, "Program failed")
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::Runtime, error.kind());
+        assert_eq!("Program failed", error.to_string());
+
+        let causes: Vec<String> = error.causes().map(|cause| cause.to_string()).collect();
+        assert_eq!(
+            vec![
+                String::from("Invalid instruction"),
+                String::from("unexpected token 'x'")
+            ],
+            causes
+        );
+    }
+
+    #[test]
+    fn test_input_error_kind() {
+        let error: Error = crate::input::Error::ParseError(String::from("invalid digit")).into();
+        assert_eq!(ErrorKind::Parse, error.kind());
+
+        let error: Error = crate::input::Error::<String>::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "not found",
+        ))
+        .into();
+        assert_eq!(ErrorKind::Io, error.kind());
+    }
+}
@@ -9,11 +9,26 @@
 //! let strings: Vec<String> = FileReader::new().read_from_file("string_input.txt").unwrap();
 //! let doubles: Vec<f64> = FileReader::new().read_from_file("double_input.txt").unwrap();
 //! ```
+//!
+//! There are also a few readers for input that doesn't follow the one-value-per-line layout:
+//! ```no_run
+//! use util::input::{FileReader, FromFile, KeyValues};
+//!
+//! // "1 2 3 4" => [1, 2, 3, 4]
+//! let numbers: Vec<usize> = FileReader::new().separator(' ').read_separated("numbers.txt").unwrap();
+//! // "pos=<-1,2,3>, r=4" => [[-1, 2, 3, 4]]
+//! let integers: Vec<Vec<i64>> = FileReader::new().read_integers("integers.txt").unwrap();
+//! // "depth: 510"
+//! let header: KeyValues = FileReader::new().read_from_file("header.txt").unwrap();
+//! let depth: usize = header.get("depth").unwrap();
+//! ```
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
+use lazy_static::lazy_static;
 use regex::Regex;
 
 /// Generic trait to read from file and into a destination of type `T`.
@@ -46,6 +61,15 @@ impl<E: std::fmt::Display> std::fmt::Display for Error<E> {
     }
 }
 
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl<E> From<std::io::Error> for Error<E> {
     fn from(error: std::io::Error) -> Self {
         Error::IoError(error)
@@ -81,6 +105,142 @@ impl FileReader {
             ..self
         }
     }
+
+    /// Reads a file consisting of a single line of values separated by the item separator
+    /// (default: comma) into a `Vec<T>`. Empty items (e.g. from repeated spaces) are skipped.
+    ///
+    /// # Failures
+    /// Returns an error if the specified file cannot be opened or contains invalid UTF-8,
+    /// if it contains more than one non-empty line, or if an item cannot be parsed into `T`.
+    pub fn read_separated<T, P>(&self, path: P) -> Result<Vec<T>, Error<T::Err>>
+    where
+        T: std::str::FromStr,
+        P: AsRef<Path>,
+    {
+        let buffer: String = self.read_from_file(path)?;
+
+        let mut lines = buffer.lines().filter(|line| !line.trim().is_empty());
+        let line = lines.next().unwrap_or("");
+        if lines.next().is_some() {
+            return Err(Error::FormatError(
+                "expected a single line, got more".to_string(),
+            ));
+        }
+
+        line.split(self.item_separator)
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<T>().map_err(Error::ParseError))
+            .collect()
+    }
+
+    /// Reads all integers contained in each line of a file, ignoring any other characters.
+    /// Returns one `Vec<T>` per line (empty lines result in empty vectors).
+    ///
+    /// # Failures
+    /// Returns an error if the specified file cannot be opened or contains invalid UTF-8.
+    /// Also returns an error if an integer cannot be parsed into `T` (e.g. a negative number
+    /// into an unsigned type).
+    pub fn read_integers<T, P>(&self, path: P) -> Result<Vec<Vec<T>>, Error<T::Err>>
+    where
+        T: std::str::FromStr,
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        reader
+            .lines()
+            .map(|line| parse_integers(&line?).map_err(Error::ParseError))
+            .collect()
+    }
+}
+
+/// Extracts all integers (including a leading sign, if any) contained in `s`, ignoring any
+/// other characters.
+///
+/// Note that a `-` directly following a digit is treated as the sign of the next number,
+/// i.e. `"1-3"` results in `[1, -3]`.
+///
+/// # Failures
+/// Returns an error if one of the integers cannot be parsed into `T`.
+pub fn parse_integers<T>(s: &str) -> Result<Vec<T>, T::Err>
+where
+    T: std::str::FromStr,
+{
+    lazy_static! {
+        static ref REGEX: Regex = Regex::new(r"[-+]?\d+").unwrap();
+    }
+
+    REGEX
+        .find_iter(s)
+        .map(|m| m.as_str().parse::<T>())
+        .collect()
+}
+
+/// Lazily parses each line read from `reader` into a `T`, trimming surrounding whitespace.
+///
+/// This is the streaming counterpart to reading a file into a `Vec<T>`: only one line is held
+/// in memory at a time, so it can be used for inputs too large to be read at once.
+///
+/// # Failures
+/// Each item is an error if the line cannot be read (e.g. invalid UTF-8) or parsed into `T`.
+pub fn parse_lines<T, R>(reader: R) -> impl Iterator<Item = Result<T, Error<T::Err>>>
+where
+    T: std::str::FromStr,
+    R: BufRead,
+{
+    reader
+        .lines()
+        .map(|line| line?.trim().parse().map_err(Error::ParseError))
+}
+
+/// Key-value pairs read from lines of the form `key: value`, as used for headers like
+/// `depth: 510`.
+#[derive(Debug)]
+pub struct KeyValues {
+    values: HashMap<String, String>,
+    item_separator: char,
+}
+
+impl KeyValues {
+    /// Returns the raw value for `key`, if present.
+    pub fn get_raw(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    /// Parses the value for `key` into `T`.
+    ///
+    /// # Failures
+    /// Returns an error if `key` is missing or if its value cannot be parsed into `T`.
+    pub fn get<T>(&self, key: &str) -> Result<T, Error<T::Err>>
+    where
+        T: std::str::FromStr,
+    {
+        self.lookup(key)?.parse::<T>().map_err(Error::ParseError)
+    }
+
+    /// Parses the value for `key` as a list of items separated by the item separator of the
+    /// `FileReader` that read the file (default: comma), e.g. `target: 10,10`.
+    ///
+    /// # Failures
+    /// Returns an error if `key` is missing or if one of the items cannot be parsed into `T`.
+    pub fn get_list<T>(&self, key: &str) -> Result<Vec<T>, Error<T::Err>>
+    where
+        T: std::str::FromStr,
+    {
+        self.lookup(key)?
+            .split(self.item_separator)
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<T>().map_err(Error::ParseError))
+            .collect()
+    }
+
+    fn lookup<E>(&self, key: &str) -> Result<&str, Error<E>> {
+        self.get_raw(key)
+            .ok_or_else(|| Error::FormatError(format!("missing key \"{}\"", key)))
+    }
 }
 
 /// Read input into a `Vec<T>`. Input is assumed to be a list of values that can be parsed into `T`
@@ -197,3 +357,98 @@ where
         }
     }
 }
+
+/// Read input into `KeyValues`. Each non-empty line is expected to be of the form `key: value`.
+impl FromFile<KeyValues> for FileReader {
+    type Error = Error<std::convert::Infallible>;
+
+    /// Takes a file path and tries to read the file content into `KeyValues`.
+    ///
+    /// # Failures
+    /// Returns an error if the specified file cannot be opened or contains invalid UTF-8.
+    /// Also returns an error if a line doesn't contain a `:` or if a key occurs more than once.
+    fn read_from_file<P: AsRef<Path>>(&self, path: P) -> Result<KeyValues, Self::Error> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        let mut values = HashMap::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, ':');
+            let key = parts.next().unwrap_or("").trim();
+            let value = match parts.next() {
+                Some(value) => value.trim(),
+                None => {
+                    return Err(Error::FormatError(format!(
+                        "line {}: expected \"key: value\", got \"{}\"",
+                        i + 1,
+                        line
+                    )));
+                }
+            };
+
+            if values.insert(key.to_string(), value.to_string()).is_some() {
+                return Err(Error::FormatError(format!(
+                    "line {}: duplicate key \"{}\"",
+                    i + 1,
+                    key
+                )));
+            }
+        }
+
+        Ok(KeyValues {
+            values,
+            item_separator: self.item_separator,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_integers() {
+        let integers: Vec<i64> = parse_integers("pos=<0,-12,3>, r=+4").unwrap();
+        assert_eq!(vec![0, -12, 3, 4], integers);
+
+        let integers: Vec<i64> = parse_integers("no numbers here").unwrap();
+        assert!(integers.is_empty());
+
+        assert!(parse_integers::<usize>("x=-1").is_err());
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let input = std::io::Cursor::new("+1\n -2 \n+3");
+        let values: Vec<i64> = parse_lines(input).collect::<Result<_, _>>().unwrap();
+        assert_eq!(vec![1, -2, 3], values);
+
+        let mut values = parse_lines::<i64, _>(std::io::Cursor::new("+1\nx\n+3"));
+        assert!(values.next().unwrap().is_ok());
+        assert!(values.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_key_values() {
+        let mut values = HashMap::new();
+        values.insert("depth".to_string(), "510".to_string());
+        values.insert("target".to_string(), "10, 12".to_string());
+        let key_values = KeyValues {
+            values,
+            item_separator: ',',
+        };
+
+        assert_eq!(510, key_values.get::<usize>("depth").unwrap());
+        assert_eq!(
+            vec![10, 12],
+            key_values.get_list::<usize>("target").unwrap()
+        );
+        assert!(key_values.get::<usize>("target").is_err());
+        assert!(key_values.get::<usize>("width").is_err());
+    }
+}
@@ -1,3 +1,7 @@
+pub mod circular;
+pub mod cli;
+pub mod error;
 pub mod input;
+pub mod random;
 pub mod rectangle;
 pub mod string;
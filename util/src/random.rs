@@ -0,0 +1,113 @@
+//! Small, seedable pseudo random number generator (SplitMix64) for generating puzzle inputs.
+//!
+//! The sequence only depends on the seed, so generated inputs are reproducible.
+//!
+//! # Examples
+//! ```
+//! use util::random::Rng;
+//!
+//! let mut rng = Rng::new(42);
+//! let value = rng.range(-10, 10);
+//! assert!(value >= -10 && value < 10);
+//! assert_eq!(Rng::new(42).range(-10, 10), value);
+//! ```
+
+/// Seedable pseudo random number generator.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create new `Rng` from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next pseudo random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in the range `low..high`.
+    ///
+    /// # Panics
+    /// Panics if `low >= high`.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        let span = high.wrapping_sub(low) as u64;
+        low.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    /// Returns a value in the range `low..high`.
+    ///
+    /// # Panics
+    /// Panics if `low >= high`.
+    pub fn range_usize(&mut self, low: usize, high: usize) -> usize {
+        assert!(low < high, "empty range {}..{}", low, high);
+        low + (self.next_u64() % (high - low) as u64) as usize
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// Returns a random element of `items`, or `None` if `items` is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.range_usize(0, items.len())])
+        }
+    }
+
+    /// Shuffles `items` in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range_usize(0, i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reproducible() {
+        let mut rng1 = Rng::new(7);
+        let mut rng2 = Rng::new(7);
+        for _ in 0..100 {
+            assert_eq!(rng1.next_u64(), rng2.next_u64());
+        }
+        assert_ne!(Rng::new(7).next_u64(), Rng::new(8).next_u64());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let value = rng.range(-3, 4);
+            assert!((-3..4).contains(&value));
+            let value = rng.range_usize(5, 6);
+            assert_eq!(5, value);
+        }
+        assert!(rng.range(i64::MIN, i64::MAX) < i64::MAX);
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut rng = Rng::new(3);
+        let mut items: Vec<usize> = (0..50).collect();
+        rng.shuffle(&mut items);
+        assert_ne!((0..50).collect::<Vec<_>>(), items);
+        items.sort();
+        assert_eq!((0..50).collect::<Vec<_>>(), items);
+    }
+}
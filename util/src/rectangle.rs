@@ -1,58 +1,465 @@
 //! Defines a rectangle and provides various rectangle-related helpers
+//!
+//! Rectangles are half-open: a rectangle at `(x, y)` with width `w` and height `h` covers all
+//! points `(px, py)` with `x <= px < x + w` and `y <= py < y + h`, i.e. `w * h` unit cells.
+//!
+//! # Examples
+//! ```
+//! use util::rectangle::{self, Rectangle};
+//!
+//! let a = Rectangle::new(-2, -2, 4, 4);
+//! let b = Rectangle::new(0, 0, 4, 4);
+//! assert_eq!(Some(Rectangle::new(0, 0, 2, 2)), a.intersection(&b));
+//! assert_eq!(28, rectangle::union_area(&[a, b]));
+//! ```
+//!
+//! `GridIndex` answers collision queries on larger sets of rectangles:
+//! ```
+//! use util::rectangle::{GridIndex, Rectangle};
+//!
+//! let mut index = GridIndex::new(4);
+//! let a = index.insert(Rectangle::new(-2, -2, 4, 4));
+//! let b = index.insert(Rectangle::new(0, 0, 4, 4));
+//! let c = index.insert(Rectangle::new(10, 10, 1, 1));
+//! assert_eq!(vec![a, b], index.covering(1, 1));
+//! assert_eq!(vec![(a, b)], index.overlapping_pairs());
+//! assert!(index.overlapping(&Rectangle::new(5, 5, 5, 5)).is_empty());
+//! ```
+
+use std::collections::HashMap;
 
 /// Rectangle definition
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Rectangle {
     /// Horizontal position of top-left corner.
-    x: usize,
+    x: i64,
     /// Vertical position of top-left corner (+y points down).
-    y: usize,
+    y: i64,
     /// Width of rectangle.
-    width: usize,
+    width: u64,
     /// Height of rectangle.
-    height: usize,
+    height: u64,
 }
 
 impl Rectangle {
     /// Create new `Rectangle`
-    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
-        Self {
+    ///
+    /// # Panics
+    /// Panics if the right or bottom edge doesn't fit in an `i64`; see `Rectangle::checked_new`.
+    pub fn new(x: i64, y: i64, width: u64, height: u64) -> Self {
+        Self::checked_new(x, y, width, height).expect("rectangle edge out of range")
+    }
+
+    /// Create new `Rectangle`, or returns `None` if its right edge (`x + width`) or bottom edge
+    /// (`y + height`) doesn't fit in an `i64`.
+    pub fn checked_new(x: i64, y: i64, width: u64, height: u64) -> Option<Self> {
+        x.checked_add_unsigned(width)?;
+        y.checked_add_unsigned(height)?;
+        Some(Self {
             x,
             y,
             width,
             height,
+        })
+    }
+
+    /// Create new `Rectangle` from its edges (`right` and `bottom` are exclusive). Returns
+    /// `None` if `right < left` or `bottom < top`.
+    pub fn from_edges(left: i64, top: i64, right: i64, bottom: i64) -> Option<Self> {
+        if right < left || bottom < top {
+            return None;
         }
+        Some(Self {
+            x: left,
+            y: top,
+            width: right.abs_diff(left),
+            height: bottom.abs_diff(top),
+        })
     }
 
     /// Returns horizontal position of top-left corner.
-    pub fn x(&self) -> usize {
+    pub fn x(&self) -> i64 {
         self.x
     }
 
     /// Returns vertical position of top-left corner.
-    pub fn y(&self) -> usize {
+    pub fn y(&self) -> i64 {
         self.y
     }
 
     /// Returns width of rectangle.
-    pub fn width(&self) -> usize {
+    pub fn width(&self) -> u64 {
         self.width
     }
 
     /// Returns height of rectangle.
-    pub fn height(&self) -> usize {
+    pub fn height(&self) -> u64 {
         self.height
     }
 
+    /// Returns horizontal position of the right edge (exclusive).
+    pub fn right(&self) -> i64 {
+        // Fits by construction, even if `width` itself doesn't fit in an `i64`
+        self.x.wrapping_add_unsigned(self.width)
+    }
+
+    /// Returns vertical position of the bottom edge (exclusive).
+    pub fn bottom(&self) -> i64 {
+        self.y.wrapping_add_unsigned(self.height)
+    }
+
+    /// Returns the area of the rectangle, which may not fit in a `u64`.
+    pub fn area(&self) -> u128 {
+        u128::from(self.width) * u128::from(self.height)
+    }
+
+    /// Returns whether the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
     /// Returns whether `self` and `other` collide.
     pub fn collides_with(&self, other: &Rectangle) -> bool {
-        other.x < self.x + self.width
-            && other.x + other.width > self.x
-            && other.y < self.y + self.height
-            && other.y + other.height > self.y
+        other.x < self.right()
+            && other.right() > self.x
+            && other.y < self.bottom()
+            && other.bottom() > self.y
+    }
+
+    /// Returns whether the point `(x, y)` lies within `self`.
+    pub fn contains_point(&self, x: i64, y: i64) -> bool {
+        self.x <= x && x < self.right() && self.y <= y && y < self.bottom()
+    }
+
+    /// Returns whether `other` lies completely within `self`.
+    pub fn contains(&self, other: &Rectangle) -> bool {
+        self.x <= other.x
+            && other.right() <= self.right()
+            && self.y <= other.y
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Returns the area shared by `self` and `other`, or `None` if they don't collide.
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        if !self.collides_with(other) {
+            return None;
+        }
+        Rectangle::from_edges(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        )
+    }
+
+    /// Returns the smallest rectangle containing both `self` and `other`.
+    pub fn bounding_box(&self, other: &Rectangle) -> Rectangle {
+        Rectangle::from_edges(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+        .unwrap()
+    }
+
+    /// Returns the area covered by `self` but not by `other`, as (at most four) disjoint,
+    /// non-empty rectangles.
+    pub fn subtract(&self, other: &Rectangle) -> Vec<Rectangle> {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None if self.is_empty() => return Vec::new(),
+            None => return vec![*self],
+        };
+
+        let pieces = [
+            // Above and below the overlap, over the full width
+            Rectangle::from_edges(self.x, self.y, self.right(), overlap.y),
+            Rectangle::from_edges(self.x, overlap.bottom(), self.right(), self.bottom()),
+            // Left and right of the overlap, within its height
+            Rectangle::from_edges(self.x, overlap.y, overlap.x, overlap.bottom()),
+            Rectangle::from_edges(overlap.right(), overlap.y, self.right(), overlap.bottom()),
+        ];
+        pieces
+            .iter()
+            .flatten()
+            .filter(|piece| !piece.is_empty())
+            .copied()
+            .collect()
     }
 }
 
+/// Index over a set of rectangles for collision queries.
+///
+/// The plane is divided into square cells of a fixed size, and every rectangle is registered in
+/// each cell it covers, so queries only need to look at the rectangles sharing a cell with the
+/// query. This works well as long as the cell size is in the order of the typical rectangle
+/// size; see `GridIndex::from_rectangles`. Rectangles covering more than `MAX_CELLS` cells are
+/// kept in a separate list instead and checked by every query, so a few outliers don't blow up
+/// the memory usage. Empty rectangles are stored, but never collide with anything.
+#[derive(Debug, Clone)]
+pub struct GridIndex {
+    cell_size: i64,
+    rectangles: Vec<Rectangle>,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    /// Ids of the rectangles covering more than `MAX_CELLS` cells, sorted.
+    large: Vec<usize>,
+}
+
+impl GridIndex {
+    /// Maximum number of cells a rectangle is registered in.
+    pub const MAX_CELLS: u64 = 256;
+
+    /// Create new, empty `GridIndex` with cells of `cell_size` x `cell_size`.
+    ///
+    /// # Panics
+    /// Panics if `cell_size` is zero.
+    pub fn new(cell_size: u64) -> Self {
+        assert!(cell_size > 0, "cell size must be positive");
+        Self {
+            cell_size: cell_size.min(i64::MAX as u64) as i64,
+            rectangles: Vec::new(),
+            cells: HashMap::new(),
+            large: Vec::new(),
+        }
+    }
+
+    /// Create new `GridIndex` containing `rectangles` (with ids in iteration order), choosing
+    /// the cell size from their median dimensions, so a few huge rectangles don't make the
+    /// cells too coarse for all others.
+    pub fn from_rectangles<I>(rectangles: I) -> Self
+    where
+        I: IntoIterator<Item = Rectangle>,
+    {
+        let rectangles: Vec<Rectangle> = rectangles.into_iter().collect();
+        let mut sizes: Vec<u64> = rectangles
+            .iter()
+            .filter(|r| !r.is_empty())
+            .map(|r| r.width.max(r.height))
+            .collect();
+        let middle = sizes.len() / 2;
+        let cell_size = if sizes.is_empty() {
+            1
+        } else {
+            *sizes.select_nth_unstable(middle).1
+        };
+
+        let mut index = Self::new(cell_size);
+        for r in rectangles {
+            index.insert(r);
+        }
+        index
+    }
+
+    /// Returns the number of rectangles in the index.
+    pub fn len(&self) -> usize {
+        self.rectangles.len()
+    }
+
+    /// Returns whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rectangles.is_empty()
+    }
+
+    /// Returns the rectangle with the given id.
+    pub fn get(&self, id: usize) -> Option<&Rectangle> {
+        self.rectangles.get(id)
+    }
+
+    /// Inserts `rectangle` into the index and returns its id (the number of rectangles inserted
+    /// before it).
+    pub fn insert(&mut self, rectangle: Rectangle) -> usize {
+        let id = self.rectangles.len();
+        self.rectangles.push(rectangle);
+        if self.is_large(&rectangle) {
+            self.large.push(id);
+        } else {
+            for cell in self.cells_of(&rectangle) {
+                self.cells.entry(cell).or_default().push(id);
+            }
+        }
+        id
+    }
+
+    /// Returns the ids of all rectangles colliding with `rectangle`, sorted by id.
+    pub fn overlapping(&self, rectangle: &Rectangle) -> Vec<usize> {
+        if rectangle.is_empty() {
+            return Vec::new();
+        }
+        if self.is_large(rectangle) {
+            return (0..self.rectangles.len())
+                .filter(|&id| {
+                    let other = &self.rectangles[id];
+                    !other.is_empty() && other.collides_with(rectangle)
+                })
+                .collect();
+        }
+        let mut ids: Vec<usize> = self
+            .cells_of(rectangle)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .chain(self.large.iter())
+            .copied()
+            .filter(|&id| self.rectangles[id].collides_with(rectangle))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of all rectangles containing the point `(x, y)`, sorted by id.
+    pub fn covering(&self, x: i64, y: i64) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .cells
+            .get(&self.cell_of(x, y))
+            .into_iter()
+            .flatten()
+            .chain(self.large.iter())
+            .copied()
+            .filter(|&id| self.rectangles[id].contains_point(x, y))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Returns all pairs `(i, j)` with `i < j` of colliding rectangles, sorted.
+    pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for (&cell, ids) in self.cells.iter() {
+            for (n, &i) in ids.iter().enumerate() {
+                for &j in ids[n + 1..].iter() {
+                    let (a, b) = (&self.rectangles[i], &self.rectangles[j]);
+                    if !a.collides_with(b) {
+                        continue;
+                    }
+                    // Two rectangles share all cells covering their intersection; only report
+                    // the pair in the cell containing its top-left corner.
+                    let corner = self.cell_of(a.x.max(b.x), a.y.max(b.y));
+                    if corner == cell {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+        }
+        // Large rectangles aren't in any cell, so check them against all others
+        for (n, &i) in self.large.iter().enumerate() {
+            let a = &self.rectangles[i];
+            let others = (0..self.rectangles.len())
+                .filter(|j| self.large[..=n].binary_search(j).is_err())
+                .filter(|&j| {
+                    let other = &self.rectangles[j];
+                    !other.is_empty() && a.collides_with(other)
+                });
+            pairs.extend(others.map(|j| (i.min(j), i.max(j))));
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// Returns the cell containing the point `(x, y)`.
+    fn cell_of(&self, x: i64, y: i64) -> (i64, i64) {
+        (x.div_euclid(self.cell_size), y.div_euclid(self.cell_size))
+    }
+
+    /// Returns whether `rectangle` covers more than `MAX_CELLS` cells.
+    fn is_large(&self, rectangle: &Rectangle) -> bool {
+        if rectangle.is_empty() {
+            return false;
+        }
+        let (x_start, y_start) = self.cell_of(rectangle.x, rectangle.y);
+        let (x_end, y_end) = self.cell_of(rectangle.right() - 1, rectangle.bottom() - 1);
+        let columns = u128::from(x_end.abs_diff(x_start)) + 1;
+        let rows = u128::from(y_end.abs_diff(y_start)) + 1;
+        columns * rows > u128::from(Self::MAX_CELLS)
+    }
+
+    /// Returns the cells covered by `rectangle` (none if it is empty).
+    fn cells_of(&self, rectangle: &Rectangle) -> impl Iterator<Item = (i64, i64)> {
+        let corners = if rectangle.is_empty() {
+            None
+        } else {
+            Some((
+                self.cell_of(rectangle.x, rectangle.y),
+                self.cell_of(rectangle.right() - 1, rectangle.bottom() - 1),
+            ))
+        };
+        corners
+            .into_iter()
+            .flat_map(|((x_start, y_start), (x_end, y_end))| {
+                (x_start..=x_end).flat_map(move |x| (y_start..=y_end).map(move |y| (x, y)))
+            })
+    }
+}
+
+/// Returns the area covered by at least `min_count` of `rectangles`.
+///
+/// Sweeps over the x coordinates where rectangles start or end: in between, the set of
+/// rectangles crossing the sweep line doesn't change, so the covered area of such a slab is its
+/// width times the length covered on the y axis by the active rectangles. The running time only
+/// depends on the number of rectangles, not on their coordinates.
+pub fn covered_area<'a, I>(rectangles: I, min_count: usize) -> u128
+where
+    I: IntoIterator<Item = &'a Rectangle>,
+{
+    let mut by_x: Vec<&Rectangle> = rectangles.into_iter().filter(|r| !r.is_empty()).collect();
+    by_x.sort_unstable_by_key(|r| r.x);
+
+    let mut xs = Vec::with_capacity(2 * by_x.len());
+    for r in by_x.iter() {
+        xs.push(r.x);
+        xs.push(r.right());
+    }
+    xs.sort_unstable();
+    xs.dedup();
+
+    let mut by_x = by_x.into_iter().peekable();
+    let mut active: Vec<&Rectangle> = Vec::new();
+    let mut area = 0;
+    for slab in xs.windows(2) {
+        let (x_start, x_end) = (slab[0], slab[1]);
+        active.retain(|r| r.right() > x_start);
+        while let Some(r) = by_x.next_if(|r| r.x <= x_start) {
+            active.push(r);
+        }
+
+        area +=
+            u128::from(covered_length(&active, min_count)) * u128::from(x_end.abs_diff(x_start));
+    }
+
+    area
+}
+
+/// Returns the area covered by any of `rectangles`, counting overlapping parts once.
+pub fn union_area<'a, I>(rectangles: I) -> u128
+where
+    I: IntoIterator<Item = &'a Rectangle>,
+{
+    covered_area(rectangles, 1)
+}
+
+/// Returns the length on the y axis covered by at least `min_count` of `rectangles`.
+fn covered_length(rectangles: &[&Rectangle], min_count: usize) -> u64 {
+    let mut events = Vec::with_capacity(2 * rectangles.len());
+    for r in rectangles {
+        events.push((r.y, 1));
+        events.push((r.bottom(), -1));
+    }
+    events.sort_unstable();
+
+    let mut length = 0;
+    let mut depth: isize = 0;
+    let mut last_y = 0;
+    for (y, delta) in events {
+        if depth >= min_count as isize {
+            length += y.abs_diff(last_y);
+        }
+        depth += delta;
+        last_y = y;
+    }
+
+    length
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,5 +512,196 @@ mod tests {
 
         rect2.height = 1;
         assert!(!rect1.collides_with(&rect2));
+
+        // Negative origins
+        let rect3 = Rectangle::new(-5, -5, 9, 8);
+        assert!(rect1.collides_with(&rect3));
+        assert!(rect3.collides_with(&rect1));
+        assert!(!rect3.collides_with(&Rectangle::new(-10, -10, 5, 20)));
+        assert!(rect3.collides_with(&Rectangle::new(-10, -10, 6, 20)));
+    }
+
+    #[test]
+    fn test_edges_and_area() {
+        let rect = Rectangle::new(-3, 2, 7, 5);
+        assert_eq!(4, rect.right());
+        assert_eq!(7, rect.bottom());
+        assert_eq!(35, rect.area());
+        assert!(!rect.is_empty());
+        assert!(Rectangle::new(-3, 2, 0, 5).is_empty());
+
+        assert_eq!(Some(rect), Rectangle::from_edges(-3, 2, 4, 7));
+        assert_eq!(None, Rectangle::from_edges(-3, 2, -4, 7));
+
+        // Edges at the limits of `i64`
+        let wide = Rectangle::new(i64::MIN, 0, u64::MAX, 2);
+        assert_eq!((i64::MAX, 2), (wide.right(), wide.bottom()));
+        assert_eq!(2 * u128::from(u64::MAX), wide.area());
+        assert_eq!(Some(wide), Rectangle::from_edges(i64::MIN, 0, i64::MAX, 2));
+        assert!(wide.contains_point(i64::MAX - 1, 0));
+        assert_eq!(Some(wide), Rectangle::checked_new(i64::MIN, 0, u64::MAX, 2));
+        assert_eq!(None, Rectangle::checked_new(i64::MIN + 1, 0, u64::MAX, 1));
+        assert_eq!(None, Rectangle::checked_new(0, i64::MAX, 1, 1));
+        assert_eq!(None, Rectangle::checked_new(1, 0, i64::MAX as u64, 1));
+    }
+
+    #[test]
+    fn test_contains() {
+        let rect = Rectangle::new(-3, 2, 7, 5);
+        assert!(rect.contains_point(-3, 2));
+        assert!(rect.contains_point(3, 6));
+        assert!(!rect.contains_point(4, 6));
+        assert!(!rect.contains_point(3, 7));
+        assert!(!rect.contains_point(-4, 2));
+
+        assert!(rect.contains(&rect));
+        assert!(rect.contains(&Rectangle::new(-2, 3, 6, 4)));
+        assert!(!rect.contains(&Rectangle::new(-2, 3, 7, 4)));
+        assert!(!rect.contains(&Rectangle::new(-4, 3, 2, 2)));
+    }
+
+    #[test]
+    fn test_intersection_and_bounding_box() {
+        let a = Rectangle::new(-3, 2, 7, 5);
+        let b = Rectangle::new(1, -1, 7, 5);
+        assert_eq!(Some(Rectangle::new(1, 2, 3, 2)), a.intersection(&b));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+        assert_eq!(Some(a), a.intersection(&a));
+        assert_eq!(None, a.intersection(&Rectangle::new(4, 2, 1, 1)));
+
+        assert_eq!(Rectangle::new(-3, -1, 11, 8), a.bounding_box(&b));
+        assert_eq!(a, a.bounding_box(&Rectangle::new(-2, 3, 1, 1)));
+    }
+
+    #[test]
+    fn test_subtract() {
+        let a = Rectangle::new(-3, 2, 7, 5);
+        assert_eq!(vec![a], a.subtract(&Rectangle::new(10, 10, 1, 1)));
+        assert!(a.subtract(&a).is_empty());
+        assert!(a.subtract(&Rectangle::new(-5, 0, 20, 20)).is_empty());
+
+        // Hole in the middle: four pieces around it
+        let hole = Rectangle::new(-1, 3, 2, 2);
+        let pieces = a.subtract(&hole);
+        assert_eq!(4, pieces.len());
+        assert_eq!(
+            a.area() - hole.area(),
+            pieces.iter().map(|p| p.area()).sum()
+        );
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(a.contains(p));
+            assert!(!p.collides_with(&hole));
+            assert!(pieces[i + 1..].iter().all(|q| !p.collides_with(q)));
+        }
+
+        // Overlapping corner: two pieces
+        let b = Rectangle::new(1, -1, 7, 5);
+        let pieces = a.subtract(&b);
+        assert_eq!(2, pieces.len());
+        assert_eq!(29, pieces.iter().map(|p| p.area()).sum::<u128>());
+    }
+
+    #[test]
+    fn test_union_area() {
+        let a = Rectangle::new(-3, 2, 7, 5);
+        let b = Rectangle::new(1, -1, 7, 5);
+        assert_eq!(0, union_area(&[]));
+        assert_eq!(35, union_area(&[a]));
+        assert_eq!(35 + 35 - 6, union_area(&[a, b]));
+        assert_eq!(
+            35 + 35 - 6,
+            union_area(&[a, b, a, Rectangle::new(0, 0, 0, 9)])
+        );
+        assert_eq!(6, covered_area(&[a, b], 2));
+        assert_eq!(0, covered_area(&[a, b], 3));
+
+        // Areas beyond `u64`
+        let huge = Rectangle::new(0, 0, 1 << 33, 1 << 33);
+        assert_eq!(1 << 66, union_area(&[huge]));
+        let plane = Rectangle::new(i64::MIN, i64::MIN, u64::MAX, u64::MAX);
+        assert_eq!(plane.area(), union_area(&[plane, huge, a]));
+        assert_eq!((1 << 66) + 35 - 20, covered_area(&[plane, huge, a], 2));
+
+        // Cross-check against counting unit cells
+        let mut rng = crate::random::Rng::new(36);
+        let rectangles: Vec<Rectangle> = (0..30)
+            .map(|_| {
+                Rectangle::new(
+                    rng.range(-20, 20),
+                    rng.range(-20, 20),
+                    rng.range(0, 15) as u64,
+                    rng.range(0, 15) as u64,
+                )
+            })
+            .collect();
+        for min_count in 1..4 {
+            let mut cells = 0;
+            for x in -20..35 {
+                for y in -20..35 {
+                    let count = rectangles.iter().filter(|r| r.contains_point(x, y)).count();
+                    if count >= min_count {
+                        cells += 1;
+                    }
+                }
+            }
+            assert_eq!(cells, covered_area(&rectangles, min_count));
+        }
+    }
+
+    #[test]
+    fn test_grid_index() {
+        let mut rng = crate::random::Rng::new(37);
+        for &cell_size in &[1, 3, 8, 100] {
+            let mut rectangles: Vec<Rectangle> = (0..60)
+                .map(|_| {
+                    Rectangle::new(
+                        rng.range(-30, 30),
+                        rng.range(-30, 30),
+                        rng.range(0, 12) as u64,
+                        rng.range(0, 12) as u64,
+                    )
+                })
+                .collect();
+            // Rectangles covering too many cells to register them in each
+            rectangles.insert(10, Rectangle::new(-1000, -3, 2000, 6));
+            rectangles.insert(20, Rectangle::new(i64::MIN, 7, u64::MAX, 1));
+            rectangles.insert(30, Rectangle::new(3, i64::MIN, 0, u64::MAX));
+            rectangles.insert(40, Rectangle::new(-20, -20, 40, 40));
+
+            let mut index = GridIndex::new(cell_size);
+            assert!(index.is_empty());
+            for (id, r) in rectangles.iter().enumerate() {
+                assert_eq!(id, index.insert(*r));
+            }
+            assert_eq!(rectangles.len(), index.len());
+            assert_eq!(Some(&rectangles[5]), index.get(5));
+
+            let collide =
+                |a: &Rectangle, b: &Rectangle| !a.is_empty() && !b.is_empty() && a.collides_with(b);
+            let mut expected_pairs = Vec::new();
+            for (i, a) in rectangles.iter().enumerate() {
+                let expected: Vec<usize> = (0..rectangles.len())
+                    .filter(|&j| collide(a, &rectangles[j]))
+                    .collect();
+                assert_eq!(expected, index.overlapping(a));
+                expected_pairs.extend(expected.into_iter().filter(|&j| i < j).map(|j| (i, j)));
+            }
+            assert_eq!(expected_pairs, index.overlapping_pairs());
+
+            for x in -35..45 {
+                for y in -35..45 {
+                    let expected: Vec<usize> = (0..rectangles.len())
+                        .filter(|&id| rectangles[id].contains_point(x, y))
+                        .collect();
+                    assert_eq!(expected, index.covering(x, y));
+                }
+            }
+
+            let auto = GridIndex::from_rectangles(rectangles.iter().copied());
+            assert_eq!(expected_pairs, auto.overlapping_pairs());
+        }
+        assert!(GridIndex::from_rectangles(Vec::new())
+            .overlapping_pairs()
+            .is_empty());
     }
 }
@@ -1,3 +1,21 @@
+//! String helpers: edit distances, an index for finding similar strings and grapheme
+//! frequency histograms.
+//!
+//! # Examples
+//! ```
+//! use util::string::{find_similar_pairs, Metric};
+//!
+//! let ids = ["abcde", "fghij", "klmno", "fguij", "axcye"];
+//! let pairs = find_similar_pairs(&ids, 1, Metric::Hamming);
+//! assert_eq!(vec![(1, 3, 1)], pairs);
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use unicode_segmentation::{Graphemes, UnicodeSegmentation};
+
 /// Calculate the hamming distance between two strings.
 pub fn hamming_distance(a: &str, b: &str) -> usize {
     let mut distance = 0;
@@ -12,6 +30,268 @@ pub fn hamming_distance(a: &str, b: &str) -> usize {
     distance
 }
 
+/// Calculate the Levenshtein distance (minimum number of single-character insertions,
+/// deletions and substitutions) between two strings.
+pub fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, cha) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &chb) in b.iter().enumerate() {
+            let substitution = previous[j] + if cha == chb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Distance metric between two strings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Metric {
+    /// Number of differing characters at the same position (see `hamming_distance`).
+    Hamming,
+    /// Number of edits needed to transform one string into the other (see
+    /// `levenshtein_distance`).
+    Levenshtein,
+}
+
+impl Metric {
+    /// Returns the distance between `a` and `b` according to the metric.
+    pub fn distance(self, a: &str, b: &str) -> usize {
+        match self {
+            Metric::Hamming => hamming_distance(a, b),
+            Metric::Levenshtein => levenshtein_distance(a, b),
+        }
+    }
+}
+
+/// Index for looking up all strings within a small distance of a query string.
+///
+/// Every string is indexed under its neighbourhood: all variants obtainable by at most
+/// `max_distance` deletions (Levenshtein), or by truncating and masking at most `max_distance`
+/// characters in total (Hamming). Two strings within `max_distance` of each other always share
+/// a variant, so only strings sharing one have to be compared. This is efficient for small
+/// distances, as the size of the neighbourhood grows with `length ^ max_distance`.
+#[derive(Debug)]
+pub struct NeighbourhoodIndex<'a> {
+    metric: Metric,
+    max_distance: usize,
+    items: Vec<&'a str>,
+    buckets: HashMap<u64, Vec<usize>>,
+}
+
+impl<'a> NeighbourhoodIndex<'a> {
+    /// Create new, empty `NeighbourhoodIndex` for lookups within `max_distance` using `metric`.
+    pub fn new(metric: Metric, max_distance: usize) -> Self {
+        Self {
+            metric,
+            max_distance,
+            items: Vec::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of strings in the index.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Inserts `value` into the index and returns its id (the number of strings inserted
+    /// before it).
+    pub fn insert(&mut self, value: &'a str) -> usize {
+        let id = self.items.len();
+        self.items.push(value);
+        for variant in self.neighbourhood(value) {
+            self.buckets.entry(variant).or_default().push(id);
+        }
+        id
+    }
+
+    /// Returns the ids of all strings within `max_distance` of `query`, together with their
+    /// distance, sorted by id.
+    pub fn find(&self, query: &str) -> Vec<(usize, usize)> {
+        let mut candidates: Vec<usize> = self
+            .neighbourhood(query)
+            .iter()
+            .filter_map(|variant| self.buckets.get(variant))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        candidates
+            .into_iter()
+            .map(|id| (id, self.metric.distance(self.items[id], query)))
+            .filter(|&(_, distance)| distance <= self.max_distance)
+            .collect()
+    }
+
+    /// Returns the hashes of all variants in the neighbourhood of `value`.
+    fn neighbourhood(&self, value: &str) -> HashSet<u64> {
+        let mut chars: Vec<char> = value.chars().collect();
+        let mut variants = HashSet::new();
+        match self.metric {
+            Metric::Levenshtein => add_deletions(&mut chars, 0, self.max_distance, &mut variants),
+            Metric::Hamming => {
+                for truncated in 0..=self.max_distance.min(chars.len()) {
+                    let length = chars.len() - truncated;
+                    add_masks(
+                        &mut chars[..length],
+                        0,
+                        self.max_distance - truncated,
+                        &mut variants,
+                    );
+                }
+            }
+        }
+        variants
+    }
+}
+
+/// Placeholder for a masked character in a neighbourhood variant.
+const MASK: char = '\0';
+
+fn hash_variant(chars: &[char]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    chars.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Adds all variants of `chars` with at most `remaining` characters at or after `from` deleted.
+fn add_deletions(chars: &mut Vec<char>, from: usize, remaining: usize, out: &mut HashSet<u64>) {
+    out.insert(hash_variant(chars));
+    if remaining == 0 {
+        return;
+    }
+    for i in from..chars.len() {
+        let c = chars.remove(i);
+        add_deletions(chars, i, remaining - 1, out);
+        chars.insert(i, c);
+    }
+}
+
+/// Adds all variants of `chars` with at most `remaining` characters at or after `from` masked.
+fn add_masks(chars: &mut [char], from: usize, remaining: usize, out: &mut HashSet<u64>) {
+    out.insert(hash_variant(chars));
+    if remaining == 0 {
+        return;
+    }
+    for i in from..chars.len() {
+        let c = std::mem::replace(&mut chars[i], MASK);
+        add_masks(chars, i + 1, remaining - 1, out);
+        chars[i] = c;
+    }
+}
+
+/// Returns an iterator over the grapheme clusters (user-perceived characters) of `s`, i.e. the
+/// extended grapheme clusters of Unicode Standard Annex #29.
+///
+/// # Examples
+/// ```
+/// use util::string::graphemes;
+///
+/// let clusters: Vec<&str> = graphemes("e\u{301}a\u{1F1E8}\u{1F1ED}").collect();
+/// assert_eq!(vec!["e\u{301}", "a", "\u{1F1E8}\u{1F1ED}"], clusters);
+/// ```
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    s.graphemes(true)
+}
+
+/// Number of occurrences of each grapheme cluster in a string.
+///
+/// # Examples
+/// ```
+/// use util::string::Histogram;
+///
+/// let histogram = Histogram::new("bababc");
+/// assert_eq!(3, histogram.count("b"));
+/// assert!(histogram.has_multiplicity(2));
+/// assert_eq!(1, histogram.with_multiplicity(3));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram<'a> {
+    counts: HashMap<&'a str, usize>,
+}
+
+impl<'a> Histogram<'a> {
+    /// Create new `Histogram` of the grapheme clusters in `s`.
+    pub fn new(s: &'a str) -> Self {
+        let mut counts = HashMap::new();
+        for grapheme in graphemes(s) {
+            *counts.entry(grapheme).or_insert(0) += 1;
+        }
+        Self { counts }
+    }
+
+    /// Returns the number of occurrences of `grapheme`.
+    pub fn count(&self, grapheme: &str) -> usize {
+        self.counts.get(grapheme).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of distinct grapheme clusters.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns whether the histogram is empty (i.e. was created from an empty string).
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Returns an iterator over the grapheme clusters and their number of occurrences, in
+    /// arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, usize)> + '_ {
+        self.counts
+            .iter()
+            .map(|(&grapheme, &count)| (grapheme, count))
+    }
+
+    /// Returns the number of grapheme clusters occurring exactly `multiplicity` times.
+    pub fn with_multiplicity(&self, multiplicity: usize) -> usize {
+        self.counts
+            .values()
+            .filter(|&&count| count == multiplicity)
+            .count()
+    }
+
+    /// Returns whether any grapheme cluster occurs exactly `multiplicity` times.
+    pub fn has_multiplicity(&self, multiplicity: usize) -> bool {
+        self.counts.values().any(|&count| count == multiplicity)
+    }
+}
+
+/// Finds all pairs of strings in `items` within distance `max_distance` of each other.
+///
+/// Returns tuples `(i, j, distance)` of indices into `items` with `i < j`, sorted by `i`,
+/// then `j`.
+pub fn find_similar_pairs<S: AsRef<str>>(
+    items: &[S],
+    max_distance: usize,
+    metric: Metric,
+) -> Vec<(usize, usize, usize)> {
+    let mut index = NeighbourhoodIndex::new(metric, max_distance);
+    let mut pairs = Vec::new();
+    for item in items {
+        let item = item.as_ref();
+        let matches = index.find(item);
+        let j = index.insert(item);
+        pairs.extend(matches.into_iter().map(|(i, distance)| (i, j, distance)));
+    }
+
+    pairs.sort_unstable();
+    pairs
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,4 +315,103 @@ mod tests {
         assert_eq!(1, hamming_distance(&inputs[6], &inputs[7]));
         assert_eq!(1, hamming_distance(&inputs[7], &inputs[6]));
     }
+
+    #[test]
+    fn test_levenshtein_distance() {
+        assert_eq!(0, levenshtein_distance("", ""));
+        assert_eq!(3, levenshtein_distance("", "abc"));
+        assert_eq!(3, levenshtein_distance("kitten", "sitting"));
+        assert_eq!(2, levenshtein_distance("flaw", "lawn"));
+        assert_eq!(1, levenshtein_distance("fghij", "fgij"));
+        assert_eq!(3, hamming_distance("fghij", "fgij"));
+    }
+
+    #[test]
+    fn test_graphemes() {
+        let clusters = |s| graphemes(s).collect::<Vec<_>>();
+        assert!(clusters("").is_empty());
+        assert_eq!(vec!["a", "b", "c"], clusters("abc"));
+        assert_eq!(vec!["a\u{308}\u{301}", "o"], clusters("a\u{308}\u{301}o"));
+        assert_eq!(vec!["\r\n", "\n"], clusters("\r\n\n"));
+        // Family emoji (man, ZWJ, woman, ZWJ, girl) and thumbs up with skin tone
+        assert_eq!(
+            vec![
+                "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}",
+                "\u{1F44D}\u{1F3FD}"
+            ],
+            clusters("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{1F44D}\u{1F3FD}")
+        );
+        // Three regional indicators: one flag and a single indicator
+        assert_eq!(
+            vec!["\u{1F1E8}\u{1F1ED}", "\u{1F1E9}"],
+            clusters("\u{1F1E8}\u{1F1ED}\u{1F1E9}")
+        );
+        // Devanagari vowel signs, Hangul jamo
+        assert_eq!(
+            vec!["\u{915}\u{93F}", "\u{928}\u{93E}", "\u{928}\u{947}"],
+            clusters("\u{915}\u{93F}\u{928}\u{93E}\u{928}\u{947}")
+        );
+        assert_eq!(
+            vec!["\u{1100}\u{1161}\u{11A8}", "a"],
+            clusters("\u{1100}\u{1161}\u{11A8}a")
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = Histogram::new("abcccd");
+        assert_eq!(4, histogram.len());
+        assert_eq!(3, histogram.count("c"));
+        assert_eq!(0, histogram.count("x"));
+        assert!(histogram.has_multiplicity(3));
+        assert!(!histogram.has_multiplicity(2));
+        assert_eq!(3, histogram.with_multiplicity(1));
+
+        let histogram = Histogram::new("e\u{301}ee\u{301}");
+        assert_eq!(2, histogram.count("e\u{301}"));
+        assert_eq!(1, histogram.count("e"));
+        assert!(Histogram::new("").is_empty());
+    }
+
+    #[test]
+    fn test_find_similar_pairs() {
+        let items = ["abcde", "fghij", "klmno", "fguij", "axcye", "fgij", "abcde"];
+        assert_eq!(
+            vec![(0, 6, 0), (1, 3, 1)],
+            find_similar_pairs(&items, 1, Metric::Hamming)
+        );
+        assert_eq!(
+            vec![(0, 6, 0), (1, 3, 1), (1, 5, 1), (3, 5, 1)],
+            find_similar_pairs(&items, 1, Metric::Levenshtein)
+        );
+        assert!(find_similar_pairs::<&str>(&[], 1, Metric::Hamming).is_empty());
+    }
+
+    #[test]
+    fn test_find_similar_pairs_matches_brute_force() {
+        let mut rng = crate::random::Rng::new(2);
+        let items: Vec<String> = (0..300)
+            .map(|_| {
+                let len = rng.range_usize(3, 7);
+                (0..len)
+                    .map(|_| (b'a' + rng.range_usize(0, 3) as u8) as char)
+                    .collect()
+            })
+            .collect();
+
+        for &metric in &[Metric::Hamming, Metric::Levenshtein] {
+            for max_distance in 0..3 {
+                let mut expected = Vec::new();
+                for i in 0..items.len() {
+                    for j in i + 1..items.len() {
+                        let distance = metric.distance(&items[i], &items[j]);
+                        if distance <= max_distance {
+                            expected.push((i, j, distance));
+                        }
+                    }
+                }
+                assert_eq!(expected, find_similar_pairs(&items, max_distance, metric));
+            }
+        }
+    }
 }